@@ -0,0 +1,7 @@
+//! the web worker running the checks over the wallet, away from the page
+
+use cardano_web_wallet_analyser::Analyser;
+
+fn main() {
+    Analyser::register();
+}
@@ -1,13 +1,49 @@
-use crate::{context::ContextHandle, wallet::Wallet};
+use crate::{
+    context::{Action, ContextHandle, Page},
+    inspector::AddressInspector,
+    wallet::{Session, Source, Wallet, WalletsDashboard},
+};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
 use yew::prelude::*;
 
 #[function_component(AppBody)]
 pub fn body() -> Html {
     let ctx = use_context::<ContextHandle>().unwrap();
 
-    if let Some(wallet) = ctx.wallet.as_ref() {
-        // Handle wallet-related logic here
-        html! { < Wallet wallet={wallet.clone()} /> }
+    let inspecting = ctx.page == Page::InspectAddress;
+    let wallet_page = wallet_page(&ctx);
+
+    // the wallet page stays mounted (hidden) while inspecting an address
+    // so the analysis is not lost when coming back to it
+    html! {
+        <>
+            if inspecting {
+                <AddressInspector />
+            }
+            <div class={classes!(inspecting.then_some("d-none"))}>
+                {wallet_page}
+            </div>
+        </>
+    }
+}
+
+fn wallet_page(ctx: &ContextHandle) -> Html {
+    if let Some(session) = ctx.replay.as_ref() {
+        html! { <Wallet source={Source::Replay(session.clone())} /> }
+    } else if !ctx.wallets.is_empty() {
+        // keyed so disconnecting a wallet does not scan the other ones again
+        html! {
+            <>
+                if ctx.wallets.len() > 1 {
+                    <WalletsDashboard />
+                }
+                { for ctx.wallets.iter().map(|wallet| html! {
+                    <Wallet key={wallet.name().to_string()} source={Source::Extension(wallet.clone())}
+                        connection={ctx.connections.get(&*wallet.name()).cloned().unwrap_or_default()} />
+                }) }
+            </>
+        }
     } else {
         html! {
             <>
@@ -57,7 +93,56 @@ pub fn body() -> Html {
                         </div>
                     </div>
                 </div>
+
+                <LoadSession />
             </>
         }
     }
 }
+
+/// replay a session saved from the API activity panel
+#[function_component(LoadSession)]
+fn load_session() -> Html {
+    let ctx = use_context::<ContextHandle>().unwrap();
+    let error = use_state(|| None::<String>);
+
+    let onchange = {
+        let error = error.clone();
+        move |event: Event| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            let file = gloo::file::File::from(file);
+            let ctx = ctx.clone();
+            let error = error.clone();
+
+            spawn_local(async move {
+                let result = gloo::file::futures::read_as_text(&file)
+                    .await
+                    .map_err(|error| error.to_string())
+                    .and_then(|text| Session::from_json(&text));
+
+                match result {
+                    Ok(session) => ctx.dispatch(Action::SessionLoaded(session)),
+                    Err(message) => error.set(Some(message)),
+                }
+            });
+        }
+    };
+
+    html! {
+        <div class="card mb-4">
+            <div class="card-body">
+                <h5 class="card-title">{"Replay a recorded session"}</h5>
+                <p class="card-text">
+                    {"Analyse the responses saved from a wallet (API activity panel, \"Save session\") without connecting it."}
+                </p>
+                <input class="form-control form-control-sm" type="file" accept="application/json" onchange={onchange} />
+                if let Some(error) = &*error {
+                    <div class="text-danger small mt-1">{format!("Could not load the session: {error}")}</div>
+                }
+            </div>
+        </div>
+    }
+}
@@ -1,15 +1,41 @@
-use cardano_connector::Wallet;
-use std::rc::Rc;
+use crate::wallet::{CancelToken, Session, WalletSummary};
+use cardano_connector::{Address, Wallet};
+use std::{
+    collections::{BTreeMap, HashSet},
+    rc::Rc,
+};
 use yew::{prelude::*, Reducible, UseReducerHandle};
 
 pub enum Action {
+    // connects the wallet, or disconnects it if it was connected already
     WalletSelected(Wallet),
+    // a recorded session to replay instead of a wallet extension
+    SessionLoaded(Session),
+    PageSelected(Page),
+    // the result of the scan of a connected wallet, by wallet name
+    WalletScanned(Rc<WalletSummary>),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Page {
+    #[default]
+    Wallet,
+    InspectAddress,
 }
 
 #[derive(PartialEq)]
 pub struct Context {
-    // once a wallet is selected this will be set.
-    pub wallet: Option<Wallet>,
+    // the wallets selected, in the order they were connected, identified
+    // by their name like their summaries and connections
+    pub wallets: Vec<Wallet>,
+    pub replay: Option<Rc<Session>>,
+    pub page: Page,
+    pub summaries: Rc<BTreeMap<String, Rc<WalletSummary>>>,
+    // cancelled when the wallet is deselected, or a session is loaded,
+    // interrupting its scan and its watcher, by wallet name
+    pub connections: Rc<BTreeMap<String, CancelToken>>,
+    // the addresses known to the connected wallets, once they were scanned
+    pub wallet_addresses: Rc<HashSet<Address>>,
 }
 
 pub type ContextHandle = UseReducerHandle<Context>;
@@ -17,11 +43,47 @@ pub type ContextHandle = UseReducerHandle<Context>;
 impl Context {
     pub fn new() -> Self {
         crate::style::initialize_theme();
-        Self { wallet: None }
+        Self::reset()
     }
 
     fn reset() -> Self {
-        Self { wallet: None }
+        Self {
+            wallets: Vec::new(),
+            replay: None,
+            page: Page::default(),
+            summaries: Rc::default(),
+            connections: Rc::default(),
+            wallet_addresses: Rc::default(),
+        }
+    }
+
+    fn with_summaries(
+        wallets: Vec<Wallet>,
+        replay: Option<Rc<Session>>,
+        page: Page,
+        summaries: BTreeMap<String, Rc<WalletSummary>>,
+        connections: Rc<BTreeMap<String, CancelToken>>,
+    ) -> Self {
+        let wallet_addresses = summaries
+            .values()
+            .flat_map(|summary| summary.addresses.iter().chain(&summary.reward_addresses))
+            .cloned()
+            .collect();
+
+        Self {
+            wallets,
+            replay,
+            page,
+            summaries: Rc::new(summaries),
+            connections,
+            wallet_addresses: Rc::new(wallet_addresses),
+        }
+    }
+
+    fn disconnect_all(&self) {
+        for connection in self.connections.values() {
+            connection.cancel();
+        }
     }
 }
 
@@ -30,20 +92,59 @@ impl Reducible for Context {
     fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
         match action {
             Action::WalletSelected(wallet) => {
-                let toggle = self
-                    .wallet
-                    .as_ref()
-                    .map(|old| old.icon() == wallet.icon())
-                    .unwrap_or_default();
-
-                if toggle {
-                    Rc::new(Self::reset())
+                let mut wallets = self.wallets.clone();
+                let mut summaries = (*self.summaries).clone();
+                let mut connections = (*self.connections).clone();
+
+                if let Some(index) = wallets.iter().position(|old| old.name() == wallet.name()) {
+                    let old = wallets.remove(index);
+                    summaries.remove(&*old.name());
+                    if let Some(connection) = connections.remove(&*old.name()) {
+                        connection.cancel();
+                    }
                 } else {
-                    Rc::new(Self {
-                        wallet: Some(wallet),
-                        ..Self::reset()
-                    })
+                    connections.insert(wallet.name().to_string(), CancelToken::default());
+                    wallets.push(wallet);
+                }
+
+                if wallets.is_empty() {
+                    return Rc::new(Self::reset());
                 }
+                // a replayed session is not shown next to the wallets
+                Rc::new(Self::with_summaries(
+                    wallets,
+                    None,
+                    Page::default(),
+                    summaries,
+                    Rc::new(connections),
+                ))
+            }
+            Action::SessionLoaded(session) => {
+                self.disconnect_all();
+                Rc::new(Self {
+                    replay: Some(Rc::new(session)),
+                    ..Self::reset()
+                })
+            }
+            Action::PageSelected(page) => Rc::new(Self {
+                wallets: self.wallets.clone(),
+                replay: self.replay.clone(),
+                page,
+                summaries: self.summaries.clone(),
+                connections: self.connections.clone(),
+                wallet_addresses: self.wallet_addresses.clone(),
+            }),
+            Action::WalletScanned(summary) => {
+                let mut summaries = (*self.summaries).clone();
+                summaries.insert(summary.name.clone(), summary);
+
+                Rc::new(Self::with_summaries(
+                    self.wallets.clone(),
+                    self.replay.clone(),
+                    self.page,
+                    summaries,
+                    self.connections.clone(),
+                ))
             }
         }
     }
@@ -0,0 +1,249 @@
+//! decode any address pasted by the user
+//!
+//! The address can be given in bech32 (Shelley addresses), base58 (Byron
+//! addresses) or as the hex of its bytes.
+
+use crate::context::ContextHandle;
+use cardano_connector::Address;
+use pallas_addresses::{
+    byron::{AddrAttrProperty, AddrType},
+    ByronAddress, Network, ShelleyDelegationPart, ShelleyPaymentPart, StakeAddress, StakePayload,
+};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Bech32,
+    Base58,
+    Hex,
+}
+
+/// decode the address, trying each of the encodings in turn
+fn parse(input: &str) -> Option<(Encoding, Address)> {
+    let input = input.trim();
+
+    Address::from_bech32(input)
+        .map(|address| (Encoding::Bech32, address))
+        .or_else(|_| {
+            ByronAddress::from_base58(input).map(|address| (Encoding::Base58, address.into()))
+        })
+        .or_else(|_| Address::from_hex(input).map(|address| (Encoding::Hex, address)))
+        .ok()
+}
+
+#[function_component(AddressInspector)]
+pub fn address_inspector() -> Html {
+    let ctx = use_context::<ContextHandle>().unwrap();
+    let input = use_state(String::new);
+
+    let oninput = {
+        let input = input.clone();
+        move |event: InputEvent| {
+            let element: HtmlInputElement = event.target_unchecked_into();
+            input.set(element.value());
+        }
+    };
+
+    let result = if input.trim().is_empty() {
+        html! {}
+    } else if let Some((encoding, address)) = parse(&input) {
+        view_address(encoding, &address, &ctx)
+    } else {
+        html! {
+            <div class="alert alert-danger" role="alert">
+                {"This is not a valid bech32, base58 or hex encoded Cardano address."}
+            </div>
+        }
+    };
+
+    html! {
+        <div class="card">
+            <div class="card-header">
+                <h3 class="card-title mb-0">{"Inspect address"}</h3>
+            </div>
+            <div class="card-body">
+                <input
+                    type="text"
+                    class="form-control mb-3 font-monospace"
+                    placeholder="addr1..., stake1..., Ae2..., DdzFF... or hex"
+                    value={(*input).clone()}
+                    oninput={oninput} />
+                {result}
+            </div>
+        </div>
+    }
+}
+
+fn view_address(encoding: Encoding, address: &Address, ctx: &ContextHandle) -> Html {
+    let stake_address = stake_address(address);
+
+    let ownership = if ctx.wallet_addresses.is_empty() {
+        html! {
+            <span class="text-muted">{"Connect a wallet to check if the address belongs to it."}</span>
+        }
+    } else if ctx.wallet_addresses.contains(address) {
+        html! { <span class="badge text-bg-success">{"Belongs to the connected wallet"}</span> }
+    } else if stake_address
+        .as_ref()
+        .is_some_and(|stake| ctx.wallet_addresses.contains(stake))
+    {
+        html! { <span class="badge text-bg-warning">{"Delegates to the stake key of the connected wallet"}</span> }
+    } else {
+        html! { <span class="badge text-bg-secondary">{"Not known to the connected wallet"}</span> }
+    };
+
+    let mut rows = vec![
+        ("Encoding", encoding.to_string()),
+        ("Header type", header_type(address)),
+        (
+            "Network",
+            address.network().map(network).unwrap_or_default(),
+        ),
+        ("Bytes (hex)", address.to_hex()),
+    ];
+    if let Ok(bech32) = address.to_bech32() {
+        rows.push(("Bech32", bech32));
+    }
+
+    match address {
+        Address::Shelley(shelley) => {
+            rows.push(("Payment credential", payment(shelley.payment())));
+            rows.push(("Delegation part", delegation(shelley.delegation())));
+        }
+        Address::Stake(stake) => {
+            rows.push(("Stake credential", stake_payload(stake.payload())));
+        }
+        Address::Byron(byron) => rows.extend(byron_attributes(byron)),
+    }
+
+    if let Some(stake) = &stake_address {
+        rows.push(("Stake address", stake.to_bech32().unwrap_or_default()));
+    }
+
+    html! {
+        <>
+            <div class="mb-3">{ownership}</div>
+            <table class="table table-sm">
+                <tbody>
+                    { for rows.into_iter().map(|(label, value)| html! {
+                        <tr>
+                            <th style="width: 200px;">{label}</th>
+                            <td class="font-monospace text-break">{value}</td>
+                        </tr>
+                    }) }
+                </tbody>
+            </table>
+        </>
+    }
+}
+
+/// the reward address of the stake credential of the address, if it has one
+fn stake_address(address: &Address) -> Option<Address> {
+    match address {
+        Address::Shelley(shelley) => StakeAddress::try_from(shelley.clone())
+            .ok()
+            .map(Address::Stake),
+        Address::Stake(_) => Some(address.clone()),
+        Address::Byron(_) => None,
+    }
+}
+
+fn header_type(address: &Address) -> String {
+    let description = match address.typeid() {
+        0 => "base address, key payment and key stake",
+        1 => "base address, script payment and key stake",
+        2 => "base address, key payment and script stake",
+        3 => "base address, script payment and script stake",
+        4 => "pointer address, key payment",
+        5 => "pointer address, script payment",
+        6 => "enterprise address, key payment",
+        7 => "enterprise address, script payment",
+        8 => "Byron address",
+        14 => "reward address, key stake",
+        15 => "reward address, script stake",
+        _ => "unknown",
+    };
+    format!("{} ({description})", address.typeid())
+}
+
+fn network(network: Network) -> String {
+    match network {
+        Network::Mainnet => "Mainnet".to_owned(),
+        Network::Testnet => "Testnet".to_owned(),
+        Network::Other(tag) => format!("Other ({tag})"),
+    }
+}
+
+fn payment(payment: &ShelleyPaymentPart) -> String {
+    match payment {
+        ShelleyPaymentPart::Key(hash) => format!("key hash {hash}"),
+        ShelleyPaymentPart::Script(hash) => format!("script hash {hash}"),
+    }
+}
+
+fn delegation(delegation: &ShelleyDelegationPart) -> String {
+    match delegation {
+        ShelleyDelegationPart::Key(hash) => format!("stake key hash {hash}"),
+        ShelleyDelegationPart::Script(hash) => format!("stake script hash {hash}"),
+        ShelleyDelegationPart::Pointer(pointer) => format!(
+            "pointer (slot {}, tx {}, cert {})",
+            pointer.slot(),
+            pointer.tx_idx(),
+            pointer.cert_idx()
+        ),
+        ShelleyDelegationPart::Null => "none".to_owned(),
+    }
+}
+
+fn stake_payload(payload: &StakePayload) -> String {
+    match payload {
+        StakePayload::Stake(hash) => format!("stake key hash {hash}"),
+        StakePayload::Script(hash) => format!("stake script hash {hash}"),
+    }
+}
+
+fn byron_attributes(byron: &ByronAddress) -> Vec<(&'static str, String)> {
+    let payload = match byron.decode() {
+        Ok(payload) => payload,
+        Err(error) => return vec![("Byron payload", format!("cannot be decoded: {error}"))],
+    };
+
+    let address_type = match payload.addrtype {
+        AddrType::PubKey => "public key".to_owned(),
+        AddrType::Script => "script".to_owned(),
+        AddrType::Redeem => "redeem".to_owned(),
+        AddrType::Other(t) => format!("other ({t})"),
+    };
+
+    let mut rows = vec![
+        ("Byron root", payload.root.to_string()),
+        ("Byron address type", address_type),
+    ];
+    for attribute in payload.attributes.iter() {
+        rows.push(match attribute {
+            AddrAttrProperty::AddrDistr(_) => ("Byron stake distribution", "set".to_owned()),
+            AddrAttrProperty::DerivationPath(path) => (
+                "Byron derivation path",
+                format!("encrypted, {} bytes", path.len()),
+            ),
+            AddrAttrProperty::NetworkTag(tag) => ("Byron network tag", hex::encode(tag.as_slice())),
+            AddrAttrProperty::Unparsed(key, value) => (
+                "Byron attribute",
+                format!("{key}: {}", hex::encode(value.as_slice())),
+            ),
+        });
+    }
+
+    rows
+}
+
+impl std::fmt::Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bech32 => f.write_str("bech32"),
+            Self::Base58 => f.write_str("base58 (Byron)"),
+            Self::Hex => f.write_str("hex"),
+        }
+    }
+}
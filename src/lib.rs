@@ -4,6 +4,7 @@
 mod body;
 mod context;
 pub mod icons;
+mod inspector;
 mod style;
 mod wallet;
 
@@ -11,8 +12,9 @@ pub use self::{
     body::AppBody,
     context::{AppContextProvider, ContextProviderProps},
     icons::*,
+    wallet::{install_demo_wallets, Analyser},
 };
-use context::Action;
+use context::{Action, Page};
 use yew::prelude::*;
 
 #[derive(Properties, PartialEq)]
@@ -28,10 +30,9 @@ fn WalletButton(props: &WalletButtonProps) -> Html {
     let wallet_app_icon = props.wallet.icon();
 
     let selected = ctx
-        .wallet
-        .as_ref()
-        .map(|wallet| wallet.name() == wallet_app_name)
-        .unwrap_or_default();
+        .wallets
+        .iter()
+        .any(|wallet| wallet.name() == wallet_app_name);
 
     let onclick = {
         let ctx = ctx.clone();
@@ -67,7 +68,9 @@ pub fn wallet_list() -> Html {
                     <p>{"We couldn't find any CIP30-compatible wallets installed in your browser. "}
                     {"To interact with this application, please install "}
                     <a href="https://www.lace.io" class="alert-link">{"Lace wallet"}</a>
-                    {" and try again."}</p>
+                    {" and try again, or "}
+                    <a href="?demo" class="alert-link">{"try the demo wallets"}</a>
+                    {"."}</p>
                 </div>
             </div>
         }
@@ -87,3 +90,35 @@ pub fn wallet_list() -> Html {
         }
     }
 }
+
+/// the links to the pages of the application
+#[function_component(Navigation)]
+pub fn navigation() -> Html {
+    let ctx = use_context::<context::ContextHandle>().unwrap();
+
+    let link = |page: Page, label: &'static str| {
+        let onclick = {
+            let ctx = ctx.clone();
+            move |event: MouseEvent| {
+                event.prevent_default();
+                ctx.dispatch(Action::PageSelected(page))
+            }
+        };
+        let active = if ctx.page == page {
+            Some("active")
+        } else {
+            None
+        };
+
+        html! {
+            <a class={classes!("nav-link", active)} href="#" onclick={onclick}>{label}</a>
+        }
+    };
+
+    html! {
+        <>
+            {link(Page::Wallet, "Wallet")}
+            {link(Page::InspectAddress, "Inspect address")}
+        </>
+    }
+}
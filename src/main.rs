@@ -1,4 +1,6 @@
-use cardano_web_wallet_analyser::{icons, AppBody, AppContextProvider, WalletList};
+use cardano_web_wallet_analyser::{
+    icons, install_demo_wallets, AppBody, AppContextProvider, Navigation, WalletList,
+};
 use yew::prelude::*;
 
 #[function_component]
@@ -10,6 +12,9 @@ fn App() -> Html {
                 <a class="navbar-brand" href="#">
                     {"Cardano Wallet Analyser"}
                 </a>
+                <div class="navbar-nav">
+                    <Navigation />
+                </div>
                 <div class="navbar-nav ms-auto">
                     <a
                         class="nav-link"
@@ -34,5 +39,7 @@ fn App() -> Html {
 }
 
 fn main() {
+    // before the wallets are listed
+    install_demo_wallets();
     yew::Renderer::<App>::new().render();
 }
@@ -0,0 +1,394 @@
+//! run the checks in a web worker
+//!
+//! Decoding and hashing tens of thousands of UTxOs freezes the page when
+//! done on the UI thread. The page sends the [`SnapshotRecord`] of the scan
+//! to the [`Analyser`] worker which decodes the outputs of the UTxOs for the
+//! UTxO table, then runs the [`Check`]s one after the other and streams
+//! their findings back.
+//!
+//! The findings refer to the UTxOs by their position in the snapshot so
+//! they are cheap to send back, the page rebuilds the [`AlertMessage`]s
+//! from its own snapshot.
+
+use super::{
+    cancel::{until_cancelled, CancelToken},
+    output::decode_output,
+    protocol_parameters::ProtocolParameters,
+    snapshot::{SnapshotRecord, WalletSnapshot},
+    tasks::{
+        accounts::{Account, AccountsReport},
+        change_address::{ChangeAddressIssue, ChangeAddressReport},
+        collateral::{CollateralIssue, CollateralReport},
+        spam::{SpamReason, SuspiciousUtxo},
+        AlertMessage, Check,
+    },
+    token_lists::TokenLists,
+    utxo_table::utxo_id,
+    utxos::DecodedUtxos,
+};
+use cardano_connector::{Address, Utxo};
+use futures::{future::LocalBoxFuture, FutureExt, SinkExt, StreamExt};
+use gloo::{
+    timers::future::TimeoutFuture,
+    worker::reactor::{Reactor, ReactorRegistrar, ReactorScope, ReactorSpawner},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context as TaskContext, Poll},
+};
+use yew::prelude::*;
+
+/// the loader of the worker, generated by trunk next to the page
+const WORKER_LOADER: &str = "analysis_worker_loader.js";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnalysisRequest {
+    pub snapshot: SnapshotRecord,
+    pub parameters: ProtocolParameters,
+    pub token_lists: TokenLists,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum AnalysisEvent {
+    /// the outputs of the UTxOs, sent before the checks are run
+    Decoded(DecodedUtxos),
+    Started(Check),
+    Finished(Check, Finding),
+    Failed(Check),
+    /// the snapshot could not be decoded, no check was run
+    Invalid(String),
+}
+
+/// the findings of a check, the UTxOs given by their position in the snapshot
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Finding {
+    /// the hex of the addresses and their UTxOs
+    ByAddress(Vec<(String, Vec<usize>)>),
+    Suspicious(Vec<(usize, Vec<SpamReason>)>),
+    DatumsAndScripts(Vec<usize>),
+    Collateral(Vec<CollateralIssue>),
+    Accounts(Vec<AccountFinding>),
+    ChangeAddress(Vec<ChangeAddressIssue>),
+    /// the checks the worker does not run
+    None,
+}
+
+/// an [`Account`], its addresses given by their hex
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountFinding {
+    reward_address: Option<String>,
+    known: bool,
+    addresses: Vec<String>,
+    utxos: Vec<usize>,
+}
+
+/// how far the analysis went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+/// the worker running the checks, see [`analyse`]
+pub struct Analyser(LocalBoxFuture<'static, ()>);
+
+impl Reactor for Analyser {
+    type Scope = ReactorScope<AnalysisRequest, AnalysisEvent>;
+
+    fn create(scope: Self::Scope) -> Self {
+        Self(run(scope).boxed_local())
+    }
+}
+
+impl Future for Analyser {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<()> {
+        self.0.as_mut().poll(cx)
+    }
+}
+
+impl Analyser {
+    /// to be called by the worker binary
+    pub fn register() {
+        ReactorRegistrar::<Self>::new().register();
+    }
+}
+
+async fn run(mut scope: ReactorScope<AnalysisRequest, AnalysisEvent>) {
+    while let Some(request) = scope.next().await {
+        let snapshot = match WalletSnapshot::from_record(request.snapshot) {
+            Ok(snapshot) => snapshot,
+            Err(error) => {
+                if scope.send(AnalysisEvent::Invalid(error)).await.is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+        let decoded = DecodedUtxos::new(&snapshot.utxos);
+        if scope.send(AnalysisEvent::Decoded(decoded)).await.is_err() {
+            return;
+        }
+        let positions = positions(&snapshot.utxos);
+
+        for check in Check::ALL {
+            if scope.send(AnalysisEvent::Started(check)).await.is_err() {
+                return;
+            }
+            let event = match check.run(&snapshot, &request.parameters, &request.token_lists) {
+                Ok(alert) => AnalysisEvent::Finished(check, Finding::new(alert, &positions)),
+                Err(()) => AnalysisEvent::Failed(check),
+            };
+            if scope.send(event).await.is_err() {
+                return;
+            }
+            // let the worker handle the page going away between two checks
+            TimeoutFuture::new(0).await;
+        }
+    }
+}
+
+/// run the checks over the snapshot in a new worker
+///
+/// The events are given to `on_event` until all the checks ran or the
+/// token is cancelled, which terminates the worker.
+pub async fn analyse(
+    snapshot: &WalletSnapshot,
+    cancel: &CancelToken,
+    on_event: impl Fn(AnalysisEvent),
+) {
+    let mut bridge = ReactorSpawner::<Analyser>::new().spawn_with_loader(WORKER_LOADER);
+    bridge.send_input(AnalysisRequest {
+        snapshot: snapshot.record(),
+        parameters: ProtocolParameters::load(),
+        token_lists: TokenLists::load(),
+    });
+
+    let mut remaining = Check::ALL.len();
+    while remaining > 0 {
+        let Ok(Some(event)) = until_cancelled(bridge.next(), cancel).await else {
+            break;
+        };
+        match &event {
+            AnalysisEvent::Finished(..) | AnalysisEvent::Failed(_) => remaining -= 1,
+            AnalysisEvent::Invalid(_) => remaining = 0,
+            AnalysisEvent::Decoded(_) | AnalysisEvent::Started(_) => (),
+        }
+        on_event(event);
+    }
+}
+
+/// the position of each UTxO in the snapshot
+fn positions(utxos: &[Utxo]) -> HashMap<String, usize> {
+    let mut positions = HashMap::with_capacity(utxos.len());
+    for (position, utxo) in utxos.iter().enumerate() {
+        positions.entry(utxo_id(utxo)).or_insert(position);
+    }
+    positions
+}
+
+impl Finding {
+    fn new(alert: AlertMessage, positions: &HashMap<String, usize>) -> Self {
+        let position = |utxo: &Utxo| positions.get(&utxo_id(utxo)).copied();
+        let by_address = |utxos: HashMap<Address, Vec<Utxo>>| {
+            Self::ByAddress(
+                utxos
+                    .into_iter()
+                    .map(|(address, utxos)| {
+                        (
+                            address.to_hex(),
+                            utxos.iter().filter_map(position).collect(),
+                        )
+                    })
+                    .collect(),
+            )
+        };
+
+        match alert {
+            AlertMessage::UTxOsWithByronAddress { utxos }
+            | AlertMessage::UTxOsWithUnknownRewardAddresses { utxos }
+            | AlertMessage::UTxOsWithUsedAddresses { utxos }
+            | AlertMessage::UTxOsWithPointerAddresses { utxos }
+            | AlertMessage::UTxOsWithEnterpriseAddresses { utxos }
+            | AlertMessage::UTxOsWithScriptCredentials { utxos } => by_address(utxos),
+            AlertMessage::SuspiciousUTxOs { utxos } => Self::Suspicious(
+                utxos
+                    .into_iter()
+                    .filter_map(|suspicious| {
+                        position(&suspicious.utxo).map(|position| (position, suspicious.reasons))
+                    })
+                    .collect(),
+            ),
+            AlertMessage::UTxOsWithDatumsOrScripts { utxos } => Self::DatumsAndScripts(
+                utxos
+                    .iter()
+                    .filter_map(|(utxo, _)| position(utxo))
+                    .collect(),
+            ),
+            AlertMessage::Collateral { report } => Self::Collateral(report.issues),
+            AlertMessage::ChangeAddress { report } => Self::ChangeAddress(report.issues),
+            AlertMessage::Accounts { report } => Self::Accounts(
+                report
+                    .accounts
+                    .into_iter()
+                    .map(|account| AccountFinding {
+                        reward_address: account.reward_address.as_ref().map(Address::to_hex),
+                        known: account.known,
+                        addresses: account.addresses.iter().map(Address::to_hex).collect(),
+                        utxos: account.utxos.iter().filter_map(position).collect(),
+                    })
+                    .collect(),
+            ),
+            AlertMessage::Network { .. }
+            | AlertMessage::WalletAddresses { .. }
+            | AlertMessage::APIError { .. } => Self::None,
+        }
+    }
+
+    /// the alert of the check, with the UTxOs of the page's snapshot
+    pub fn into_alert(self, check: Check, snapshot: &WalletSnapshot) -> Option<AlertMessage> {
+        let utxo = |position: usize| snapshot.utxos.get(position).cloned();
+
+        let alert = match self {
+            Self::ByAddress(found) => {
+                let utxos = found
+                    .into_iter()
+                    .filter_map(|(address, positions)| {
+                        let address = Address::from_hex(&address).ok()?;
+                        Some((address, positions.into_iter().filter_map(utxo).collect()))
+                    })
+                    .collect();
+                match check {
+                    Check::ByronAddresses => AlertMessage::UTxOsWithByronAddress { utxos },
+                    Check::EnterpriseAddresses => {
+                        AlertMessage::UTxOsWithEnterpriseAddresses { utxos }
+                    }
+                    Check::ScriptCredentials => AlertMessage::UTxOsWithScriptCredentials { utxos },
+                    Check::PointerAddresses => AlertMessage::UTxOsWithPointerAddresses { utxos },
+                    Check::RewardKeys => AlertMessage::UTxOsWithUnknownRewardAddresses { utxos },
+                    Check::UsedAddresses => AlertMessage::UTxOsWithUsedAddresses { utxos },
+                    Check::DatumsAndScripts
+                    | Check::Collateral
+                    | Check::Spam
+                    | Check::Accounts
+                    | Check::ChangeAddress => return None,
+                }
+            }
+            Self::Suspicious(found) => AlertMessage::SuspiciousUTxOs {
+                utxos: found
+                    .into_iter()
+                    .filter_map(|(position, reasons)| {
+                        Some(SuspiciousUtxo {
+                            utxo: utxo(position)?,
+                            reasons,
+                        })
+                    })
+                    .collect(),
+            },
+            // only the flagged outputs are decoded again
+            Self::DatumsAndScripts(positions) => AlertMessage::UTxOsWithDatumsOrScripts {
+                utxos: positions
+                    .into_iter()
+                    .filter_map(utxo)
+                    .filter_map(|utxo| {
+                        let output = decode_output(&utxo).ok()?;
+                        Some((utxo, output))
+                    })
+                    .collect(),
+            },
+            Self::Collateral(issues) => AlertMessage::Collateral {
+                report: CollateralReport {
+                    collateral: snapshot.collateral.clone().unwrap_or_default(),
+                    issues,
+                },
+            },
+            Self::Accounts(accounts) => AlertMessage::Accounts {
+                report: AccountsReport {
+                    accounts: accounts
+                        .into_iter()
+                        .map(|account| Account {
+                            reward_address: account
+                                .reward_address
+                                .and_then(|address| Address::from_hex(&address).ok()),
+                            known: account.known,
+                            addresses: account
+                                .addresses
+                                .iter()
+                                .filter_map(|address| Address::from_hex(address).ok())
+                                .collect(),
+                            utxos: account.utxos.into_iter().filter_map(utxo).collect(),
+                        })
+                        .collect(),
+                },
+            },
+            Self::ChangeAddress(issues) => AlertMessage::ChangeAddress {
+                report: ChangeAddressReport {
+                    address: snapshot.change_address.clone(),
+                    issues,
+                },
+            },
+            Self::None => return None,
+        };
+        Some(alert)
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct AnalysisProgressProperties {
+    pub checks: Rc<Vec<(Check, CheckStatus)>>,
+}
+
+/// the progress of the checks, hidden once they all succeeded
+#[function_component]
+pub fn AnalysisProgress(props: &AnalysisProgressProperties) -> Html {
+    let checks = &props.checks;
+    let finished = checks
+        .iter()
+        .filter(|(_, status)| matches!(status, CheckStatus::Done | CheckStatus::Failed))
+        .count();
+    let failed = checks
+        .iter()
+        .any(|(_, status)| *status == CheckStatus::Failed);
+
+    if finished == checks.len() && !failed {
+        return html! {};
+    }
+
+    let percent = if checks.is_empty() {
+        100
+    } else {
+        finished * 100 / checks.len()
+    };
+
+    html! {
+        <div class="my-3">
+            <div class="d-flex justify-content-between small mb-1">
+                <span>{"Analysing the wallet"}</span>
+                <span class="text-muted">{format!("{finished} of {} checks", checks.len())}</span>
+            </div>
+            <div class="progress mb-2" role="progressbar" aria-valuenow={percent.to_string()} aria-valuemin="0" aria-valuemax="100">
+                <div class="progress-bar" style={format!("width: {percent}%")}></div>
+            </div>
+            <div class="d-flex flex-wrap gap-1">
+                { for checks.iter().map(|(check, status)| {
+                    let (class, icon) = match status {
+                        CheckStatus::Pending => ("text-bg-secondary", ""),
+                        CheckStatus::Running => ("text-bg-primary", "⏳ "),
+                        CheckStatus::Done => ("text-bg-success", "✓ "),
+                        CheckStatus::Failed => ("text-bg-danger", "✗ "),
+                    };
+                    html! {
+                        <span class={classes!("badge", class)}>{format!("{icon}{}", check.label())}</span>
+                    }
+                }) }
+            </div>
+        </div>
+    }
+}
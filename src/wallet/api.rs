@@ -0,0 +1,526 @@
+//! the CIP-30 API of the connected wallet, with every call recorded
+//!
+//! All the calls made to the wallet go through [`Api`] so they can be
+//! listed in the API activity panel and help diagnosing slow or flaky
+//! wallets. The responses are kept so the session can be saved and
+//! replayed later, see [`Session`].
+//!
+//! The calls are abandoned after the timeout of the [`ApiPolicy`], or when
+//! the scan is cancelled. The calls failing with an internal error of the
+//! wallet are made again, after a delay doubling at each retry, within the
+//! same timeout. A call timing out is not made again: its promise is still
+//! pending and the wallet would have to answer both.
+//!
+//! `getUtxos` and `getUsedAddresses` are asked for page after page, each
+//! page being a call of its own, until the wallet has no more.
+
+use super::{
+    cancel::{deadline, sleep, CancelToken, Interrupted},
+    cip30::{WalletApi, WalletError},
+    network_id::NetworkId,
+    policy::ApiPolicy,
+    session::{Response, Session},
+};
+use js_sys::{Array, Object, Reflect};
+use pallas_codec::minicbor;
+use serde::Serialize;
+use std::{fmt, rc::Rc};
+use wasm_bindgen::{JsCast, JsValue};
+use yew::Callback;
+
+/// the number of items asked for in each page
+const PAGE_LIMIT: u32 = 100;
+
+/// where the responses to the calls come from
+#[derive(Clone, PartialEq)]
+pub enum Backend {
+    /// the wallet extension
+    Live(WalletApi),
+    /// the responses recorded during a previous session
+    Replay(Rc<Session>),
+}
+
+#[derive(Clone)]
+pub struct Api {
+    backend: Backend,
+    /// the component, or task, making the calls
+    caller: &'static str,
+    on_call: Callback<Call>,
+    policy: ApiPolicy,
+    cancel: CancelToken,
+}
+
+/// a call made to the CIP-30 API
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Call {
+    pub caller: &'static str,
+    pub method: &'static str,
+    pub parameters: String,
+    /// 0 for the first attempt, then the number of the retry
+    pub attempt: u32,
+    /// when the call was made, in milliseconds since the page was loaded
+    pub started: f64,
+    /// in milliseconds
+    pub duration: f64,
+    /// size of the response, in bytes
+    pub response_size: Option<usize>,
+    pub error: Option<String>,
+    /// the response, to be saved in the session
+    #[serde(skip)]
+    pub response: Response,
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    Wallet(WalletError),
+    /// the wallet could not be enabled
+    Enable(String),
+    /// the wallet did not answer in time, `after` is in milliseconds
+    Timeout {
+        method: &'static str,
+        after: u32,
+    },
+    /// the user cancelled the scan
+    Cancelled,
+    /// the error the wallet returned when the session was recorded
+    Recorded(String),
+    /// the call was not made when the session was recorded
+    NotRecorded(String),
+    /// the response is not the one expected by the call
+    Invalid(String),
+}
+
+impl Api {
+    pub fn new(
+        backend: Backend,
+        caller: &'static str,
+        on_call: Callback<Call>,
+        policy: ApiPolicy,
+        cancel: CancelToken,
+    ) -> Self {
+        Self {
+            backend,
+            caller,
+            on_call,
+            policy,
+            cancel,
+        }
+    }
+
+    /// the responses are decoded by the caller, see [`Response`]
+    pub async fn all_utxos(&self) -> Result<Response, ApiError> {
+        // getUtxos(amount: cbor<value> = undefined, paginate: Paginate = undefined)
+        self.paginated(
+            "getUtxos",
+            |paginate| Array::of2(&JsValue::UNDEFINED, paginate),
+            Response::Utxos,
+        )
+        .await
+    }
+
+    pub async fn collateral(&self, amount: u64) -> Result<Response, ApiError> {
+        // getCollateral(params: { amount: cbor<Coin> })
+        let params = Object::new();
+        let amount_cbor = minicbor::to_vec(amount).expect("encoding into a Vec is infallible");
+        let _ = Reflect::set(&params, &"amount".into(), &hex::encode(amount_cbor).into());
+        self.call(
+            "getCollateral",
+            format!("amount: {amount}"),
+            Array::of1(&params),
+            |value| hex_items(value).map(Response::Utxos),
+        )
+        .await
+    }
+
+    pub async fn balance(&self) -> Result<Response, ApiError> {
+        self.call("getBalance", String::new(), Array::new(), |value| {
+            hex(value).map(Response::Balance)
+        })
+        .await
+    }
+
+    pub async fn network_id(&self) -> Result<Response, ApiError> {
+        self.call("getNetworkId", String::new(), Array::new(), |value| {
+            value
+                .as_f64()
+                .filter(|id| id.fract() == 0.0 && (0.0..=f64::from(u8::MAX)).contains(id))
+                .map(|id| Response::NetworkId(NetworkId(id as u8)))
+                .ok_or_else(|| format!("expected a network id, found {value:?}"))
+        })
+        .await
+    }
+
+    pub async fn extensions(&self) -> Result<Response, ApiError> {
+        // getExtensions(): Promise<{ cip: number }[]>
+        self.call("getExtensions", String::new(), Array::new(), |value| {
+            let items: &Array = value
+                .dyn_ref()
+                .ok_or_else(|| format!("expected an array, found {value:?}"))?;
+            items
+                .iter()
+                .map(|extension| {
+                    Reflect::get(&extension, &"cip".into())
+                        .ok()
+                        .and_then(|cip| cip.as_f64())
+                        .filter(|cip| {
+                            cip.fract() == 0.0 && (0.0..=f64::from(u32::MAX)).contains(cip)
+                        })
+                        .map(|cip| cip as u32)
+                        .ok_or_else(|| format!("expected an extension, found {extension:?}"))
+                })
+                .collect::<Result<_, _>>()
+                .map(Response::Extensions)
+        })
+        .await
+    }
+
+    pub async fn change_address(&self) -> Result<Response, ApiError> {
+        self.call("getChangeAddress", String::new(), Array::new(), |value| {
+            hex(value).map(Response::Address)
+        })
+        .await
+    }
+
+    pub async fn used_addresses(&self) -> Result<Response, ApiError> {
+        // getUsedAddresses(paginate: Paginate = undefined)
+        self.paginated("getUsedAddresses", Array::of1, Response::Addresses)
+            .await
+    }
+
+    pub async fn unused_addresses(&self) -> Result<Response, ApiError> {
+        self.call("getUnusedAddresses", String::new(), Array::new(), |value| {
+            hex_items(value).map(Response::Addresses)
+        })
+        .await
+    }
+
+    pub async fn reward_addresses(&self) -> Result<Response, ApiError> {
+        self.call("getRewardAddresses", String::new(), Array::new(), |value| {
+            hex_items(value).map(Response::Addresses)
+        })
+        .await
+    }
+
+    /// call the method for each page, until the wallet has no more
+    ///
+    /// The wallets not supporting the pagination, and the sessions recorded
+    /// before it was used, are called once without it.
+    async fn paginated(
+        &self,
+        method: &'static str,
+        arguments: impl Fn(&JsValue) -> Array,
+        response: fn(Vec<String>) -> Response,
+    ) -> Result<Response, ApiError> {
+        let mut pages = Pages::new(PAGE_LIMIT);
+
+        loop {
+            let page = pages.next;
+            let paginate = Object::new();
+            let _ = Reflect::set(&paginate, &"page".into(), &page.into());
+            let _ = Reflect::set(&paginate, &"limit".into(), &PAGE_LIMIT.into());
+            let result = self
+                .call(
+                    method,
+                    format!("page: {page}, limit: {PAGE_LIMIT}"),
+                    arguments(&paginate),
+                    |value| hex_items(value).map(response),
+                )
+                .await;
+
+            let items = match result {
+                Ok(items) => items.into_items().map_err(ApiError::Invalid)?,
+                // the previous page was the last one
+                Err(ApiError::Wallet(error)) if error.max_size.is_some() => break,
+                Err(ApiError::Wallet(error)) if page == 0 && error.is_invalid_request() => {
+                    return self.unpaginated(method, response).await;
+                }
+                Err(ApiError::NotRecorded(_)) if page == 0 => {
+                    return self.unpaginated(method, response).await;
+                }
+                Err(error) => return Err(error),
+            };
+            if !pages.add(items) {
+                break;
+            }
+        }
+
+        Ok(response(pages.items))
+    }
+
+    async fn unpaginated(
+        &self,
+        method: &'static str,
+        response: fn(Vec<String>) -> Response,
+    ) -> Result<Response, ApiError> {
+        self.call(method, String::new(), Array::new(), |value| {
+            hex_items(value).map(response)
+        })
+        .await
+    }
+
+    /// make the call, again if the wallet fails on its side, and record
+    /// every attempt
+    ///
+    /// The wallet's answer is turned into a [`Response`] by `response`,
+    /// the replayed sessions hold the responses already.
+    async fn call(
+        &self,
+        method: &'static str,
+        parameters: String,
+        arguments: Array,
+        response: impl Fn(&JsValue) -> Result<Response, String>,
+    ) -> Result<Response, ApiError> {
+        let timeout = self.policy.timeout(method);
+        let first_started = now();
+        let mut attempt = 0;
+
+        loop {
+            let started = now();
+            // the retries share the timeout of the call
+            let elapsed = (started - first_started).max(0.0) as u32;
+            let remaining = timeout.saturating_sub(elapsed);
+            let request = async {
+                match &self.backend {
+                    Backend::Live(wallet) => {
+                        let value = wallet.call(method, &arguments).await?;
+                        response(&value).map_err(ApiError::Invalid)
+                    }
+                    Backend::Replay(session) => session.replay(method, &parameters),
+                }
+            };
+            let result = deadline(request, remaining, &self.cancel)
+                .await
+                .unwrap_or_else(|interrupted| {
+                    Err(ApiError::interrupted(interrupted, method, timeout))
+                });
+            let duration = now() - started;
+
+            let recorded = match &result {
+                Ok(response) => response.clone(),
+                Err(error) => Response::Error(error.to_string()),
+            };
+            self.on_call.emit(Call {
+                caller: self.caller,
+                method,
+                parameters: parameters.clone(),
+                attempt,
+                started,
+                duration,
+                response_size: recorded.size(),
+                error: result.as_ref().err().map(ToString::to_string),
+                response: recorded,
+            });
+
+            let backoff = self.policy.backoff(attempt);
+            let elapsed = (now() - first_started).max(0.0) as u32;
+            match result {
+                Err(error)
+                    if error.is_transient()
+                        && attempt < self.policy.retries
+                        && elapsed.saturating_add(backoff) < timeout =>
+                {
+                    sleep(backoff, &self.cancel).await.map_err(|interrupted| {
+                        ApiError::interrupted(interrupted, method, timeout)
+                    })?;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// the items of a paginated method, gathered page after page
+#[derive(Debug)]
+struct Pages {
+    limit: u32,
+    /// the number of the page to ask for next
+    next: u32,
+    items: Vec<String>,
+}
+
+impl Pages {
+    fn new(limit: u32) -> Self {
+        Self {
+            limit,
+            next: 0,
+            items: Vec::new(),
+        }
+    }
+
+    /// add the items of the page, `false` once there is no page left
+    ///
+    /// A wallet ignoring the pagination returns all its items at each
+    /// call: more items than the limit, or the first page again.
+    fn add(&mut self, page: Vec<String>) -> bool {
+        let limit = self.limit as usize;
+        if page.len() > limit {
+            self.items = page;
+            return false;
+        }
+        if self.next > 0 && !page.is_empty() && self.items.starts_with(&page) {
+            return false;
+        }
+
+        let full = page.len() == limit;
+        self.items.extend(page);
+        self.next += 1;
+        full
+    }
+}
+
+/// a hex string, as the wallets return the CBOR and the addresses
+fn hex(value: &JsValue) -> Result<String, String> {
+    value
+        .as_string()
+        .ok_or_else(|| format!("expected a hex string, found {value:?}"))
+}
+
+/// an array of hex strings, `getUtxos` and `getCollateral` may return
+/// `null` instead of an empty array
+fn hex_items(value: &JsValue) -> Result<Vec<String>, String> {
+    if value.is_null() || value.is_undefined() {
+        return Ok(Vec::new());
+    }
+    if !Array::is_array(value) {
+        return Err(format!("expected an array, found {value:?}"));
+    }
+    Array::from(value).iter().map(|item| hex(&item)).collect()
+}
+
+impl ApiError {
+    pub fn interrupted(interrupted: Interrupted, method: &'static str, after: u32) -> Self {
+        match interrupted {
+            Interrupted::Timeout => Self::Timeout { method, after },
+            Interrupted::Cancelled => Self::Cancelled,
+        }
+    }
+
+    /// worth trying again: the wallet failed on its side
+    ///
+    /// The timeouts are not, the wallet may still answer the call that
+    /// timed out.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Wallet(error) if error.is_internal())
+    }
+}
+
+impl From<WalletError> for ApiError {
+    fn from(error: WalletError) -> Self {
+        Self::Wallet(error)
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Wallet(error) => error.fmt(f),
+            Self::Enable(error) => write!(f, "the wallet could not be connected: {error}"),
+            Self::Timeout { method, after } => write!(
+                f,
+                "the wallet did not answer {method} within {} s",
+                after / 1_000
+            ),
+            Self::Cancelled => f.write_str("the scan was cancelled"),
+            Self::Recorded(error) => write!(f, "{error} (recorded)"),
+            Self::NotRecorded(call) => {
+                write!(f, "{call} was not called when the session was recorded")
+            }
+            Self::Invalid(error) => write!(f, "invalid response: {error}"),
+        }
+    }
+}
+
+fn now() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map(|performance| performance.now())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(range: std::ops::Range<u32>) -> Vec<String> {
+        range.map(|item| format!("{item:02x}")).collect()
+    }
+
+    /// the items of a wallet answering page after page
+    fn gather(wallet: &[String], limit: u32) -> Vec<String> {
+        let mut pages = Pages::new(limit);
+        loop {
+            let start = (pages.next * limit) as usize;
+            let page = wallet
+                .iter()
+                .skip(start)
+                .take(limit as usize)
+                .cloned()
+                .collect();
+            if !pages.add(page) {
+                return pages.items;
+            }
+        }
+    }
+
+    #[test]
+    fn every_page_is_gathered() {
+        for count in [0, 1, 99, 100, 101, 250, 300] {
+            let wallet = items(0..count);
+            assert_eq!(gather(&wallet, 100), wallet, "{count} items");
+        }
+    }
+
+    #[test]
+    fn last_page_past_the_limit() {
+        let mut pages = Pages::new(2);
+        assert!(pages.add(items(0..2)));
+        assert!(pages.add(items(2..4)));
+        assert_eq!(pages.next, 2);
+        // the wallet has no item left, or throws a `PaginateError`
+        assert!(!pages.add(Vec::new()));
+        assert_eq!(pages.items, items(0..4));
+    }
+
+    #[test]
+    fn pagination_ignored() {
+        // all the items at once
+        let mut pages = Pages::new(100);
+        assert!(!pages.add(items(0..250)));
+        assert_eq!(pages.items, items(0..250));
+
+        // all the items at once, as many as the limit
+        let mut pages = Pages::new(100);
+        assert!(pages.add(items(0..100)));
+        assert!(!pages.add(items(0..100)));
+        assert_eq!(pages.items, items(0..100));
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod browser_tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn hex_item_arrays() {
+        let array = Array::of2(&"81a0".into(), &"82a0".into());
+        assert_eq!(
+            hex_items(&array),
+            Ok(vec!["81a0".to_owned(), "82a0".to_owned()])
+        );
+        assert_eq!(hex_items(&Array::new()), Ok(Vec::new()));
+    }
+
+    #[wasm_bindgen_test]
+    fn missing_items() {
+        assert_eq!(hex_items(&JsValue::NULL), Ok(Vec::new()));
+        assert_eq!(hex_items(&JsValue::UNDEFINED), Ok(Vec::new()));
+    }
+
+    #[wasm_bindgen_test]
+    fn invalid_items() {
+        assert!(hex_items(&"81a0".into()).is_err());
+        assert!(hex_items(&Array::of2(&"81a0".into(), &JsValue::from(1u32))).is_err());
+    }
+}
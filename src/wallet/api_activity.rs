@@ -0,0 +1,230 @@
+use super::{
+    api::Call,
+    dom_scope::DomScope,
+    export::download,
+    policy::ApiPolicy,
+    session::{Session, WalletInfo},
+};
+use gloo::file::ObjectUrl;
+use std::rc::Rc;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// the calls made to the CIP-30 API of the wallet, with their timings
+pub struct ApiActivity {
+    // kept until the next export so the download can complete
+    export: Option<ObjectUrl>,
+    /// used by the next scans
+    policy: ApiPolicy,
+    /// what the last redacted session could not redact
+    warning: Option<String>,
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    pub calls: Rc<Vec<Call>>,
+    /// the wallet the calls were made to, saved with the session
+    pub wallet: WalletInfo,
+}
+
+pub enum Message {
+    Export,
+    /// save the responses so they can be replayed, redacted or not
+    SaveSession {
+        redact: bool,
+    },
+    Policy(Setting, u32),
+}
+
+/// the settings of the [`ApiPolicy`] that can be changed in the panel
+#[derive(Clone, Copy)]
+pub enum Setting {
+    /// in seconds
+    Timeout,
+    /// in seconds
+    UtxosTimeout,
+    Retries,
+}
+
+impl Component for ApiActivity {
+    type Message = Message;
+    type Properties = Properties;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            export: None,
+            policy: ApiPolicy::load(),
+            warning: None,
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        ctx.props() != old_props
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::Export => match serde_json::to_string_pretty(&*ctx.props().calls) {
+                Ok(json) => self.export = download("api-activity.json", "application/json", &json),
+                Err(error) => gloo::console::error!(error.to_string()),
+            },
+            Message::SaveSession { redact } => {
+                let props = ctx.props();
+                let mut session = Session::from_calls(props.wallet.clone(), props.calls.iter());
+                self.warning = None;
+                if redact {
+                    let kept;
+                    (session, kept) = session.redact();
+                    if kept > 0 {
+                        self.warning = Some(format!(
+                            "{kept} inline datums, reference scripts or addresses could not be \
+                             redacted: the session is saved but not marked redacted, check it \
+                             before sharing it."
+                        ));
+                    }
+                }
+                match serde_json::to_string_pretty(&session) {
+                    Ok(json) => self.export = download("session.json", "application/json", &json),
+                    Err(error) => gloo::console::error!(error.to_string()),
+                }
+                return true;
+            }
+            Message::Policy(setting, value) => {
+                match setting {
+                    Setting::Timeout => self.policy.timeout = value.saturating_mul(1_000),
+                    Setting::UtxosTimeout => {
+                        self.policy
+                            .timeouts
+                            .insert("getUtxos".to_owned(), value.saturating_mul(1_000));
+                    }
+                    Setting::Retries => self.policy.retries = value,
+                }
+                if let Err(error) = self.policy.save() {
+                    gloo::console::error!(error.to_string());
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let scope = DomScope::of(ctx);
+        let calls = &ctx.props().calls;
+        let num_errors = calls.iter().filter(|call| call.error.is_some()).count();
+        let total_duration: f64 = calls.iter().map(|call| call.duration).sum();
+        let slowest = calls
+            .iter()
+            .max_by(|a, b| a.duration.total_cmp(&b.duration));
+
+        let setting = |setting: Setting| {
+            ctx.link().batch_callback(move |event: Event| {
+                let input: HtmlInputElement = event.target_unchecked_into();
+                input
+                    .value()
+                    .parse()
+                    .ok()
+                    .map(|value| Message::Policy(setting, value))
+            })
+        };
+
+        html! {
+            <div class="accordion my-4" id={scope.id("apiActivityAccordion")}>
+                <div class="accordion-item">
+                    <h2 class="accordion-header">
+                        <button class="accordion-button collapsed" type="button" data-bs-toggle="collapse" data-bs-target={scope.target("apiActivity")}>
+                            {format!("API activity ({} calls, {num_errors} errors)", calls.len())}
+                        </button>
+                    </h2>
+                    <div id={scope.id("apiActivity")} class="accordion-collapse collapse">
+                        <div class="accordion-body">
+                            <div class="d-flex justify-content-between align-items-center mb-2">
+                                <span class="small text-muted">
+                                    {format!("{total_duration:.0} ms spent waiting for the wallet.")}
+                                    if let Some(slowest) = slowest {
+                                        {format!(" Slowest call: {} ({:.0} ms).", slowest.method, slowest.duration)}
+                                    }
+                                </span>
+                                <div class="btn-group btn-group-sm">
+                                    <button class="btn btn-outline-secondary" disabled={calls.is_empty()}
+                                        onclick={ctx.link().callback(|_| Message::Export)}>
+                                        {"Export"}
+                                    </button>
+                                    <button class="btn btn-outline-secondary" disabled={calls.is_empty()}
+                                        title="Save the responses of the wallet to replay them later"
+                                        onclick={ctx.link().callback(|_| Message::SaveSession { redact: false })}>
+                                        {"Save session"}
+                                    </button>
+                                    <button class="btn btn-outline-secondary" disabled={calls.is_empty()}
+                                        title="Replace the credentials of the addresses, the transaction ids and the datum hashes before saving"
+                                        onclick={ctx.link().callback(|_| Message::SaveSession { redact: true })}>
+                                        {"Save redacted session"}
+                                    </button>
+                                </div>
+                            </div>
+                            if let Some(warning) = &self.warning {
+                                <div class="alert alert-warning small py-2">{warning}</div>
+                            }
+                            <div class="row g-2 align-items-center mb-3 small">
+                                <div class="col-auto">
+                                    <label class="col-form-label" for={scope.id("apiTimeout")}>{"Timeout (s)"}</label>
+                                </div>
+                                <div class="col-auto">
+                                    <input id={scope.id("apiTimeout")} type="number" min="1" class="form-control form-control-sm"
+                                        value={(self.policy.timeout / 1_000).to_string()} onchange={setting(Setting::Timeout)} />
+                                </div>
+                                <div class="col-auto">
+                                    <label class="col-form-label" for={scope.id("apiUtxosTimeout")}>{"getUtxos timeout (s)"}</label>
+                                </div>
+                                <div class="col-auto">
+                                    <input id={scope.id("apiUtxosTimeout")} type="number" min="1" class="form-control form-control-sm"
+                                        value={(self.policy.timeout("getUtxos") / 1_000).to_string()} onchange={setting(Setting::UtxosTimeout)} />
+                                </div>
+                                <div class="col-auto">
+                                    <label class="col-form-label" for={scope.id("apiRetries")}>{"Retries"}</label>
+                                </div>
+                                <div class="col-auto">
+                                    <input id={scope.id("apiRetries")} type="number" min="0" class="form-control form-control-sm"
+                                        value={self.policy.retries.to_string()} onchange={setting(Setting::Retries)} />
+                                </div>
+                                <div class="col-auto text-muted">
+                                    {"Used by the next scans. Only the calls failing with an internal error of the wallet are retried, within the timeout."}
+                                </div>
+                            </div>
+                            <div class="table-responsive">
+                                <table class="table table-sm table-hover">
+                                    <thead>
+                                        <tr>
+                                            <th>{"Started (ms)"}</th>
+                                            <th>{"Caller"}</th>
+                                            <th>{"Method"}</th>
+                                            <th>{"Attempt"}</th>
+                                            <th>{"Parameters"}</th>
+                                            <th>{"Duration (ms)"}</th>
+                                            <th>{"Response (bytes)"}</th>
+                                            <th>{"Error"}</th>
+                                        </tr>
+                                    </thead>
+                                    <tbody>
+                                        { for calls.iter().map(|call| html! {
+                                            <tr class={classes!(call.error.is_some().then_some("table-danger"))}>
+                                                <td>{format!("{:.0}", call.started)}</td>
+                                                <td>{call.caller}</td>
+                                                <td class="font-monospace">{call.method}</td>
+                                                <td>{call.attempt + 1}</td>
+                                                <td>{call.parameters.clone()}</td>
+                                                <td>{format!("{:.0}", call.duration)}</td>
+                                                <td>{call.response_size.map(|size| size.to_string()).unwrap_or_default()}</td>
+                                                <td>{call.error.clone().unwrap_or_default()}</td>
+                                            </tr>
+                                        }) }
+                                    </tbody>
+                                </table>
+                            </div>
+                        </div>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
@@ -1,108 +1,72 @@
-use cardano_connector::ConnectedWallet;
-use yew::{platform::spawn_local, prelude::*};
+use super::{
+    lovelace::Symbol,
+    output::{decode_output, Value},
+    snapshot::WalletSnapshot,
+};
+use std::rc::Rc;
+use yew::prelude::*;
 
-#[derive(Default)]
 pub struct WalletBalance {
-    state: BalanceState,
-}
-
-#[derive(Default)]
-enum BalanceState {
-    #[default]
-    Loading,
-    Loaded(u64),
-    Error(String),
-}
-
-pub enum Message {
-    CollectBalance,
-    BalanceCollected(u64),
-    Error(String),
+    // the native assets are totalled from the UTxOs as the balance
+    // only reports the lovelace
+    assets: Value,
 }
 
 #[derive(Properties, PartialEq)]
 pub struct WalletBalanceProperties {
-    pub wallet: ConnectedWallet,
+    pub snapshot: Rc<WalletSnapshot>,
+    #[prop_or_default]
+    pub symbol: Symbol,
 }
 
 impl Component for WalletBalance {
-    type Message = Message;
+    type Message = ();
     type Properties = WalletBalanceProperties;
 
     fn create(ctx: &Context<Self>) -> Self {
-        ctx.link().send_message(Message::CollectBalance);
-
-        Self::default()
-    }
-
-    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
-        if ctx.props().ne(old_props) {
-            ctx.link().send_message(Message::CollectBalance);
-            true
-        } else {
-            false
+        Self {
+            assets: total_assets(&ctx.props().snapshot),
         }
     }
 
-    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
-        match msg {
-            Message::CollectBalance => {
-                let link = ctx.link().clone();
-                let wallet = ctx.props().wallet.clone();
-
-                spawn_local(async move {
-                    match wallet.balance().await {
-                        Ok(balance) => {
-                            link.send_message(Message::BalanceCollected(balance));
-                        }
-                        Err(error) => {
-                            link.send_message(Message::Error(error.to_string()));
-                        }
-                    }
-                });
-
-                let prev = std::mem::replace(&mut self.state, BalanceState::Loading);
-
-                !matches!(prev, BalanceState::Loading)
-            }
-            Message::BalanceCollected(balance) => {
-                self.state = BalanceState::Loaded(balance);
-                true
-            }
-            Message::Error(error) => {
-                self.state = BalanceState::Error(error);
-                true
-            }
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        if ctx.props().snapshot != old_props.snapshot {
+            self.assets = total_assets(&ctx.props().snapshot);
         }
+        ctx.props().ne(old_props)
     }
 
-    fn view(&self, _ctx: &Context<Self>) -> Html {
-        let balance_state = match &self.state {
-            BalanceState::Loading => {
-                html! {
-                    <>
-                        <div class={classes!("spinner-border", "text-primary", "me-2")} role="status">
-                            <span class="visually-hidden">{"Loading..."}</span>
-                        </div>
-                        <span class="text-muted">{"Loading balance..."}</span>
-                    </>
-                }
-            }
-            BalanceState::Loaded(balance) => {
-                let balance_ada = format!("{:.2}", *balance as f64 / 1_000_000.0);
-                let balance_lovelace = format!("{}", balance);
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let balance_state = match &ctx.props().snapshot.balance {
+            Ok(balance) => {
+                let balance_ada = balance.display(ctx.props().symbol);
+                let assets =
+                    Some(&self.assets)
+                        .filter(|assets| assets.has_assets())
+                        .map(|assets| {
+                            html! {
+                                <ul class={classes!("list-unstyled", "small", "mb-0")}>
+                                    { for assets.assets().map(|(policy_id, name, quantity)| html! {
+                                        <li title={format!("{policy_id}.{}", name.to_hex())}>
+                                            {format!("{quantity} {name}")}
+                                        </li>
+                                    }) }
+                                </ul>
+                            }
+                        });
                 html! {
                     <>
                     <div class={classes!("fs-4", "fw-bold")}>
-                        {format!("{balance_ada} ₳")}
+                        {balance_ada.to_string()}
                     </div>
                     <div class={classes!("text-muted", "small")}>
-                        {format!("{} lovelace", balance_lovelace)}
+                        {balance.to_string()}
                     </div>
+                    {assets}
                     </>
                 }
             }
-            BalanceState::Error(error) => {
+            Err(error) => {
                 html! {
                     <>
                         <div class={classes!("fs-4", "fw-bold")}>
@@ -120,3 +84,14 @@ impl Component for WalletBalance {
         }
     }
 }
+
+fn total_assets(snapshot: &WalletSnapshot) -> Value {
+    let mut total = Value::default();
+    for utxo in &snapshot.utxos {
+        match decode_output(utxo) {
+            Ok(output) => total.add(&output.value),
+            Err(error) => gloo::console::error!(error.to_string()),
+        }
+    }
+    total
+}
@@ -0,0 +1,128 @@
+//! give up on the calls the wallet never answers
+//!
+//! The promises of some extensions never resolve: every call is raced
+//! against a timeout and against the [`CancelToken`] of the scan.
+
+use gloo::timers::future::TimeoutFuture;
+use std::{
+    cell::{Cell, RefCell},
+    future::{poll_fn, Future},
+    pin::pin,
+    rc::Rc,
+    task::{Poll, Waker},
+};
+
+/// shared by all the calls of a scan, cancelling it interrupts them all
+#[derive(Clone, Default)]
+pub struct CancelToken(Rc<CancelState>);
+
+#[derive(Default)]
+struct CancelState {
+    cancelled: Cell<bool>,
+    /// the calls waiting on the token
+    wakers: RefCell<Vec<Waker>>,
+    /// cancelling the parent cancels the token too
+    parent: Option<CancelToken>,
+}
+
+/// why the future did not complete
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interrupted {
+    Timeout,
+    Cancelled,
+}
+
+impl CancelToken {
+    /// a token cancelled with this one, or on its own
+    pub fn child(&self) -> Self {
+        Self(Rc::new(CancelState {
+            parent: Some(self.clone()),
+            ..CancelState::default()
+        }))
+    }
+
+    pub fn cancel(&self) {
+        self.0.cancelled.set(true);
+        for waker in self.0.wakers.take() {
+            waker.wake();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.get() || self.0.parent.as_ref().is_some_and(Self::is_cancelled)
+    }
+
+    fn register(&self, waker: &Waker) {
+        {
+            let mut wakers = self.0.wakers.borrow_mut();
+            if !wakers.iter().any(|registered| registered.will_wake(waker)) {
+                wakers.push(waker.clone());
+            }
+        }
+        if let Some(parent) = &self.0.parent {
+            parent.register(waker);
+        }
+    }
+}
+
+/// a token is only equal to its clones
+impl PartialEq for CancelToken {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// run the future until it completes, the timeout (in milliseconds)
+/// expires or the token is cancelled
+pub async fn deadline<T>(
+    future: impl Future<Output = T>,
+    timeout: u32,
+    cancel: &CancelToken,
+) -> Result<T, Interrupted> {
+    let mut future = pin!(future);
+    let mut timer = pin!(TimeoutFuture::new(timeout));
+
+    poll_fn(|cx| {
+        if cancel.is_cancelled() {
+            return Poll::Ready(Err(Interrupted::Cancelled));
+        }
+        if let Poll::Ready(output) = future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        if timer.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(Err(Interrupted::Timeout));
+        }
+        cancel.register(cx.waker());
+        Poll::Pending
+    })
+    .await
+}
+
+/// wait for the given milliseconds, unless the token is cancelled
+pub async fn sleep(millis: u32, cancel: &CancelToken) -> Result<(), Interrupted> {
+    match deadline(std::future::pending::<()>(), millis, cancel).await {
+        Err(Interrupted::Timeout) => Ok(()),
+        Err(Interrupted::Cancelled) => Err(Interrupted::Cancelled),
+        Ok(()) => unreachable!("the pending future never completes"),
+    }
+}
+
+/// run the future until it completes or the token is cancelled
+pub async fn until_cancelled<T>(
+    future: impl Future<Output = T>,
+    cancel: &CancelToken,
+) -> Result<T, Interrupted> {
+    let mut future = pin!(future);
+
+    poll_fn(|cx| {
+        if cancel.is_cancelled() {
+            return Poll::Ready(Err(Interrupted::Cancelled));
+        }
+        if let Poll::Ready(output) = future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        cancel.register(cx.waker());
+        Poll::Pending
+    })
+    .await
+}
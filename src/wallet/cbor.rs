@@ -0,0 +1,266 @@
+//! raw CIP-30 responses and their CBOR diagnostic tree
+//!
+//! The responses are the ones the wallet returned, see [`Response`]: the
+//! hex of the CBOR of the UTxOs and of the balance, the hex of the bytes of
+//! the addresses, and the number of the network id.
+
+use super::{
+    output::{decode_output, decode_value_bytes, has_next},
+    session::Response,
+};
+use cardano_connector::{Address, Utxo};
+use pallas_codec::minicbor::{
+    self,
+    data::Type,
+    decode::{Decoder, Error},
+};
+
+/// the tag of a CBOR encoded data item embedded in a byte string
+const ENCODED_CBOR_TAG: u64 = 24;
+
+/// the raw items returned by a CIP-30 method
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawResponse {
+    pub method: &'static str,
+    /// the error returned instead of the items
+    pub error: Option<String>,
+    pub items: Vec<RawItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawItem {
+    /// the item as returned by the wallet
+    pub raw: String,
+    /// the bytes of the hex, `None` if the item is not hex
+    pub bytes: Option<Vec<u8>>,
+    /// how the analyser understood the item
+    pub interpretation: String,
+}
+
+/// a data item of the diagnostic tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    /// position of the item in the decoded bytes
+    pub offset: usize,
+    /// number of bytes of the item, including its children
+    pub len: usize,
+    pub header: String,
+    pub children: Vec<Node>,
+}
+
+impl RawResponse {
+    pub fn new(method: &'static str, response: &Response) -> Self {
+        let (items, error) = match response {
+            Response::Utxos(items) => {
+                (items.iter().map(|item| RawItem::utxo(item)).collect(), None)
+            }
+            Response::Balance(value) => (vec![RawItem::balance(value)], None),
+            Response::NetworkId(network_id) => (
+                vec![RawItem {
+                    raw: network_id.0.to_string(),
+                    bytes: None,
+                    interpretation: network_id.to_string(),
+                }],
+                None,
+            ),
+            Response::Extensions(cips) => (
+                cips.iter()
+                    .map(|cip| RawItem {
+                        raw: cip.to_string(),
+                        bytes: None,
+                        interpretation: format!("CIP-{cip}"),
+                    })
+                    .collect(),
+                None,
+            ),
+            Response::Address(address) => (vec![RawItem::address(address)], None),
+            Response::Addresses(items) => (
+                items.iter().map(|item| RawItem::address(item)).collect(),
+                None,
+            ),
+            Response::Error(error) => (Vec::new(), Some(error.clone())),
+        };
+
+        Self {
+            method,
+            error,
+            items,
+        }
+    }
+}
+
+impl RawItem {
+    /// the item, with the interpretation of its bytes if it is hex
+    fn new(raw: &str, interpret: impl FnOnce(&[u8]) -> String) -> Self {
+        let bytes = hex::decode(raw).ok();
+        let interpretation = match &bytes {
+            Some(bytes) => interpret(bytes),
+            None => "not hex".to_owned(),
+        };
+
+        Self {
+            raw: raw.to_owned(),
+            bytes,
+            interpretation,
+        }
+    }
+
+    fn utxo(raw: &str) -> Self {
+        Self::new(raw, |bytes| {
+            let utxo: Utxo = match minicbor::decode(bytes) {
+                Ok(utxo) => utxo,
+                Err(error) => return format!("not a UTxO: {error}"),
+            };
+            match decode_output(&utxo) {
+                Ok(output) => format!("{}#{}\n{output:#?}", utxo.transaction_id(), utxo.index()),
+                Err(error) => format!(
+                    "{}#{}\nthe output cannot be decoded: {error}",
+                    utxo.transaction_id(),
+                    utxo.index()
+                ),
+            }
+        })
+    }
+
+    fn balance(raw: &str) -> Self {
+        Self::new(raw, |bytes| match decode_value_bytes(bytes) {
+            Ok(value) => format!("{value:#?}"),
+            Err(error) => format!("not a value: {error}"),
+        })
+    }
+
+    fn address(raw: &str) -> Self {
+        Self::new(raw, |_| match Address::from_hex(raw) {
+            Ok(address) => address.to_bech32().unwrap_or_else(|_| address.to_hex()),
+            Err(error) => format!("not an address: {error}"),
+        })
+    }
+}
+
+/// decode the bytes into a tree of CBOR data items
+///
+/// Fails if the bytes are not a single, well formed, CBOR data item.
+pub fn diagnose(bytes: &[u8]) -> Result<Node, Error> {
+    let mut d = Decoder::new(bytes);
+    let node = decode_node(&mut d)?;
+
+    if d.position() != bytes.len() {
+        return Err(Error::message(format!(
+            "{} trailing bytes after the data item",
+            bytes.len() - d.position()
+        )));
+    }
+
+    Ok(node)
+}
+
+fn decode_node(d: &mut Decoder<'_>) -> Result<Node, Error> {
+    let offset = d.position();
+    let mut children = Vec::new();
+
+    let header = match d.datatype()? {
+        Type::Bool => d.bool()?.to_string(),
+        Type::Null => {
+            d.null()?;
+            "null".to_owned()
+        }
+        Type::Undefined => {
+            d.undefined()?;
+            "undefined".to_owned()
+        }
+        Type::U8 | Type::U16 | Type::U32 | Type::U64 => format!("uint {}", d.u64()?),
+        Type::I8 | Type::I16 | Type::I32 | Type::I64 | Type::Int => {
+            format!("int {}", i128::from(d.int()?))
+        }
+        Type::F16 | Type::F32 => format!("float {}", d.f32()?),
+        Type::F64 => format!("float {}", d.f64()?),
+        Type::Simple => format!("simple({})", d.simple()?),
+        Type::Bytes => {
+            let bytes = d.bytes()?;
+            format!("bytes({}) h'{}'", bytes.len(), hex::encode(bytes))
+        }
+        Type::BytesIndef => {
+            let mut bytes = Vec::new();
+            for chunk in d.bytes_iter()? {
+                bytes.extend_from_slice(chunk?);
+            }
+            format!("bytes(_) {} h'{}'", bytes.len(), hex::encode(bytes))
+        }
+        Type::String => format!("text {:?}", d.str()?),
+        Type::StringIndef => {
+            let mut text = String::new();
+            for chunk in d.str_iter()? {
+                text.push_str(chunk?);
+            }
+            format!("text(_) {text:?}")
+        }
+        t @ (Type::Array | Type::ArrayIndef) => {
+            let len = d.array()?;
+            let mut index = 0;
+            while has_next(d, len, index)? {
+                children.push(decode_node(d)?);
+                index += 1;
+            }
+            match t {
+                Type::Array => format!("array({index})"),
+                _ => format!("array(_) {index} items"),
+            }
+        }
+        t @ (Type::Map | Type::MapIndef) => {
+            let len = d.map()?;
+            let mut index = 0;
+            while has_next(d, len, index)? {
+                let key = decode_node(d)?;
+                let value = decode_node(d)?;
+                children.push(Node {
+                    offset: key.offset,
+                    len: key.len + value.len,
+                    header: format!("{} =>", key.header),
+                    children: key.children.into_iter().chain([value]).collect(),
+                });
+                index += 1;
+            }
+            match t {
+                Type::Map => format!("map({index})"),
+                _ => format!("map(_) {index} entries"),
+            }
+        }
+        Type::Tag => {
+            let tag = d.tag()?.as_u64();
+            let start = d.position();
+            let mut item = decode_node(d)?;
+
+            // show the data item embedded in the byte string
+            if tag == ENCODED_CBOR_TAG {
+                let mut inner = Decoder::new(d.input());
+                inner.set_position(start);
+                if let Ok(bytes) = inner.bytes() {
+                    let bytes_offset = inner.position() - bytes.len();
+                    if let Ok(mut embedded) = diagnose(bytes) {
+                        shift(&mut embedded, bytes_offset);
+                        item.children.push(embedded);
+                    }
+                }
+            }
+
+            children.push(item);
+            format!("tag {tag}")
+        }
+        t => return Err(Error::type_mismatch(t).at(offset)),
+    };
+
+    Ok(Node {
+        offset,
+        len: d.position() - offset,
+        header,
+        children,
+    })
+}
+
+/// move the offsets of an embedded tree to the offsets in the outer bytes
+fn shift(node: &mut Node, by: usize) {
+    node.offset += by;
+    for child in &mut node.children {
+        shift(child, by);
+    }
+}
@@ -0,0 +1,243 @@
+//! the CIP-30 API object injected by the wallet extension
+//!
+//! `cardano_connector` decodes the responses of the wallet before handing
+//! them over and does not tell the errors of the wallet apart. The API
+//! object is called directly instead: the responses are kept as the wallet
+//! returned them, the hex shown in the debug panel and saved in the
+//! sessions, and decoded like the recorded ones (see [`Response`]).
+//!
+//! [`Response`]: super::session::Response
+
+use js_sys::{Array, Function, Object, Promise, Reflect};
+use std::fmt;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+/// the codes of the CIP-30 `APIError`
+pub const INVALID_REQUEST: i32 = -1;
+pub const INTERNAL_ERROR: i32 = -2;
+pub const REFUSED: i32 = -3;
+pub const ACCOUNT_CHANGE: i32 = -4;
+
+/// the API object returned by `enable()`
+#[derive(Clone, PartialEq)]
+pub struct WalletApi(JsValue);
+
+/// an error thrown by the wallet, or the promise it rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletError {
+    /// the code of the CIP-30 `APIError`, `None` for the other errors
+    pub code: Option<i32>,
+    /// the `maxSize` of the CIP-30 `PaginateError`, thrown when the page
+    /// asked for is past the last one
+    pub max_size: Option<u32>,
+    pub info: String,
+}
+
+impl WalletApi {
+    /// the API of the extension, the user is prompted if the page is not
+    /// allowed to connect yet
+    pub async fn enable(wallet: &cardano_connector::Wallet) -> Result<Self, WalletError> {
+        let injected = injected(wallet).ok_or_else(|| WalletError {
+            code: None,
+            max_size: None,
+            info: format!("{} is not in window.cardano", wallet.name()),
+        })?;
+        call(&injected, "enable", &Array::new()).await.map(Self)
+    }
+
+    /// call the method and wait for its result
+    pub async fn call(&self, method: &str, arguments: &Array) -> Result<JsValue, WalletError> {
+        call(&self.0, method, arguments).await
+    }
+
+    /// the `experimental` namespace of the API
+    pub fn experimental(&self) -> Option<JsValue> {
+        Reflect::get(&self.0, &"experimental".into())
+            .ok()
+            .filter(JsValue::is_object)
+    }
+}
+
+impl WalletError {
+    /// the wallet failed on its side, the same call may succeed later
+    pub fn is_internal(&self) -> bool {
+        self.code == Some(INTERNAL_ERROR)
+    }
+
+    /// the wallet does not support the pagination
+    pub fn is_invalid_request(&self) -> bool {
+        self.code == Some(INVALID_REQUEST)
+    }
+}
+
+async fn call(object: &JsValue, method: &str, arguments: &Array) -> Result<JsValue, WalletError> {
+    let function: Function = Reflect::get(object, &method.into())
+        .ok()
+        .and_then(|function| function.dyn_into().ok())
+        .ok_or_else(|| WalletError {
+            code: None,
+            max_size: None,
+            info: format!("the wallet does not implement {method}"),
+        })?;
+    let result = function.apply(object, arguments)?;
+    Ok(JsFuture::from(Promise::resolve(&result)).await?)
+}
+
+/// the object the extension injected in `window.cardano`
+fn injected(wallet: &cardano_connector::Wallet) -> Option<JsValue> {
+    let window = web_sys::window()?;
+    let cardano = Reflect::get(&window, &"cardano".into()).ok()?;
+    let cardano: &Object = cardano.dyn_ref()?;
+
+    Object::values(cardano).iter().find(|injected| {
+        let field = |name: &str| {
+            Reflect::get(injected, &name.into())
+                .ok()
+                .and_then(|value| value.as_string())
+        };
+        field("name").as_deref() == Some(&*wallet.name())
+            && field("icon").as_deref() == Some(&*wallet.icon())
+    })
+}
+
+impl From<JsValue> for WalletError {
+    fn from(error: JsValue) -> Self {
+        // the CIP-30 errors are objects with a code and an info, anything
+        // else may be thrown by a broken wallet
+        let field = |name: &str| {
+            error
+                .is_object()
+                .then(|| Reflect::get(&error, &name.into()).ok())
+                .flatten()
+        };
+        let code = field("code")
+            .and_then(|code| code.as_f64())
+            .map(|code| code as i32);
+        let max_size = field("maxSize")
+            .and_then(|max_size| max_size.as_f64())
+            .map(|max_size| max_size as u32);
+        let info = field("info")
+            .and_then(|info| info.as_string())
+            .or_else(|| {
+                error
+                    .dyn_ref::<js_sys::Error>()
+                    .map(|error| error.message().into())
+            })
+            .or_else(|| error.as_string())
+            .or_else(|| max_size.map(|max_size| format!("no page past {max_size}")))
+            .unwrap_or_else(|| format!("{error:?}"));
+
+        Self {
+            code,
+            max_size,
+            info,
+        }
+    }
+}
+
+impl fmt::Display for WalletError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { code, info, .. } = self;
+        match *code {
+            Some(INVALID_REQUEST) => write!(f, "invalid request: {info}"),
+            Some(INTERNAL_ERROR) => write!(f, "internal error of the wallet: {info}"),
+            Some(REFUSED) => write!(f, "the request was refused: {info}"),
+            Some(ACCOUNT_CHANGE) => write!(f, "the account changed: {info}"),
+            Some(code) => write!(f, "error {code}: {info}"),
+            None => f.write_str(info),
+        }
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod browser_tests {
+    use super::*;
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn object(fields: &[(&str, JsValue)]) -> JsValue {
+        let object = Object::new();
+        for (name, value) in fields {
+            let _ = Reflect::set(&object, &(*name).into(), value);
+        }
+        object.into()
+    }
+
+    #[wasm_bindgen_test]
+    fn api_error() {
+        let error = WalletError::from(object(&[
+            ("code", INTERNAL_ERROR.into()),
+            ("info", "the node is syncing".into()),
+        ]));
+        assert_eq!(
+            error,
+            WalletError {
+                code: Some(INTERNAL_ERROR),
+                max_size: None,
+                info: "the node is syncing".to_owned(),
+            }
+        );
+        assert!(error.is_internal());
+        assert_eq!(
+            error.to_string(),
+            "internal error of the wallet: the node is syncing"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn paginate_error() {
+        let error = WalletError::from(object(&[("maxSize", JsValue::from(3u32))]));
+        assert_eq!(error.code, None);
+        assert_eq!(error.max_size, Some(3));
+        assert!(!error.is_internal());
+    }
+
+    #[wasm_bindgen_test]
+    fn thrown_errors() {
+        let error = WalletError::from(JsValue::from(js_sys::Error::new("wallet locked")));
+        assert_eq!(error.code, None);
+        assert_eq!(error.info, "wallet locked");
+
+        let error = WalletError::from(JsValue::from("user declined"));
+        assert_eq!(error.code, None);
+        assert_eq!(error.info, "user declined");
+    }
+
+    #[wasm_bindgen_test]
+    fn injected_by_name_and_icon() {
+        let window = web_sys::window().expect("the tests run in a browser");
+        let wallet = |icon: &str| {
+            object(&[
+                ("name", "Twin".into()),
+                ("icon", icon.into()),
+                ("apiVersion", "1.0.0".into()),
+                ("enable", js_sys::Function::new_no_args("return {}").into()),
+                (
+                    "isEnabled",
+                    js_sys::Function::new_no_args("return Promise.resolve(false)").into(),
+                ),
+            ])
+        };
+        let cardano = object(&[
+            ("first", wallet("data:first")),
+            ("second", wallet("data:second")),
+        ]);
+        let _ = Reflect::set(&window, &"cardano".into(), &cardano);
+
+        let second = cardano_connector::wallets()
+            .into_iter()
+            .find(|wallet| &*wallet.icon() == "data:second")
+            .expect("the connector should list the injected wallet");
+        let injected = injected(&second).expect("the wallet should be found");
+        assert_eq!(
+            Reflect::get(&injected, &"icon".into())
+                .ok()
+                .and_then(|icon| icon.as_string()),
+            Some("data:second".to_owned())
+        );
+
+        let _ = Reflect::delete_property(&window, &"cardano".into());
+    }
+}
@@ -0,0 +1,538 @@
+//! coin selection algorithms run over the UTxOs of the wallet
+//!
+//! These simulate how a wallet would pick the inputs of a transaction
+//! paying the given amount of lovelace to a single address. The fee is
+//! estimated with the same transaction size model as the fragmentation
+//! analysis.
+
+use super::{
+    output::{output_size, Output, Value},
+    protocol_parameters::ProtocolParameters,
+};
+use cardano_connector::Utxo;
+use std::{collections::HashSet, fmt};
+
+/// size of the payment output: a base address and an ADA only value
+const PAYMENT_OUTPUT_SIZE: u64 = 70;
+
+/// bound on the number of subsets explored by the optimal selection
+const MAX_ITERATIONS: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    LargestFirst,
+    /// CIP-2 random-improve
+    RandomImprove,
+    /// fewest inputs (lowest fee) with the least change
+    Optimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    pub algorithm: Algorithm,
+    /// indices of the selected UTxOs
+    pub inputs: Vec<usize>,
+    /// the change returned to the wallet, with the native assets of the inputs
+    pub change: Value,
+    pub fee: u64,
+    pub tx_size: u64,
+    /// number of distinct addresses linked together by spending the inputs
+    pub linked_addresses: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionError {
+    /// the UTxOs don't hold enough to pay the amount and the fee
+    InsufficientFunds,
+    /// the change would be less than the minimum ADA of an output
+    ChangeBelowMinimumAda,
+    /// the browser failed to provide the random numbers of the selection
+    NoRandomness,
+}
+
+pub fn select(
+    algorithm: Algorithm,
+    parameters: &ProtocolParameters,
+    utxos: &[(Utxo, Option<Output>)],
+    target: u64,
+) -> Result<Selection, SelectionError> {
+    let picked = match algorithm {
+        Algorithm::LargestFirst => largest_first(parameters, utxos, target),
+        Algorithm::RandomImprove => random_improve(parameters, utxos, target)?,
+        Algorithm::Optimal => optimal(parameters, utxos, target),
+    }
+    .ok_or(SelectionError::InsufficientFunds)?;
+
+    let address = picked.change_address();
+    let change = Value {
+        coin: picked
+            .value
+            .coin
+            .checked_sub(picked.required(target))
+            .ok_or(SelectionError::InsufficientFunds)?,
+        assets: picked.value.assets,
+    };
+
+    if (change.coin > 0 || change.has_assets())
+        && change.coin < parameters.min_ada(output_size(address, &change))
+    {
+        return Err(SelectionError::ChangeBelowMinimumAda);
+    }
+
+    Ok(Selection {
+        algorithm,
+        linked_addresses: picked.addresses.len(),
+        inputs: picked.inputs,
+        change,
+        fee: picked.fee,
+        tx_size: picked.tx_size,
+    })
+}
+
+/// the inputs picked so far, with their running totals
+///
+/// The value of the inputs is added up as they are picked, the size of the
+/// transaction is only estimated again by [`Picked::push`]: encoding the
+/// change at each step would be quadratic in the number of inputs.
+struct Picked<'a> {
+    utxos: &'a [(Utxo, Option<Output>)],
+    inputs: Vec<usize>,
+    /// the value of the inputs, the lovelace of the undecoded ones included
+    value: Value,
+    /// the distinct addresses of the inputs, one witness each
+    addresses: HashSet<&'a [u8]>,
+    tx_size: u64,
+    fee: u64,
+}
+
+impl<'a> Picked<'a> {
+    fn new(utxos: &'a [(Utxo, Option<Output>)]) -> Self {
+        Self {
+            utxos,
+            inputs: Vec::new(),
+            value: Value::default(),
+            addresses: HashSet::new(),
+            tx_size: 0,
+            fee: 0,
+        }
+    }
+
+    fn with_inputs(
+        parameters: &ProtocolParameters,
+        utxos: &'a [(Utxo, Option<Output>)],
+        inputs: &[usize],
+    ) -> Self {
+        let mut picked = Self::new(utxos);
+        for index in inputs {
+            picked.add(*index);
+        }
+        picked.estimate(parameters);
+        picked
+    }
+
+    /// pick the input and estimate the fee of the transaction again
+    fn push(&mut self, parameters: &ProtocolParameters, index: usize) {
+        self.add(index);
+        self.estimate(parameters);
+    }
+
+    fn add(&mut self, index: usize) {
+        let (utxo, output) = &self.utxos[index];
+        match output {
+            Some(output) => {
+                self.value.add(&output.value);
+                self.addresses.insert(output.address.as_slice());
+            }
+            None => self.value.coin = self.value.coin.saturating_add(utxo.amount()),
+        }
+        self.inputs.push(index);
+    }
+
+    fn estimate(&mut self, parameters: &ProtocolParameters) {
+        let change_size = output_size(self.change_address(), &self.value) as u64;
+        self.tx_size = parameters.tx_size(
+            self.inputs.len() as u64,
+            self.addresses.len() as u64,
+            PAYMENT_OUTPUT_SIZE + change_size,
+        );
+        self.fee = parameters.fee(self.tx_size);
+    }
+
+    /// the change goes back to the address of the first input
+    fn change_address(&self) -> &'a [u8] {
+        let utxos = self.utxos;
+        self.inputs
+            .first()
+            .and_then(|index| utxos[*index].1.as_ref())
+            .map(|output| output.address.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// the amount the inputs need to cover: the target and the fee
+    fn required(&self, target: u64) -> u64 {
+        target.saturating_add(self.fee)
+    }
+
+    fn covers(&self, target: u64) -> bool {
+        !self.inputs.is_empty() && self.value.coin >= self.required(target)
+    }
+}
+
+/// the UTxOs, largest first
+fn by_amount(utxos: &[(Utxo, Option<Output>)]) -> Vec<usize> {
+    let mut candidates = (0..utxos.len()).collect::<Vec<_>>();
+    candidates.sort_by_key(|index| std::cmp::Reverse(utxos[*index].0.amount()));
+    candidates
+}
+
+fn largest_first<'a>(
+    parameters: &ProtocolParameters,
+    utxos: &'a [(Utxo, Option<Output>)],
+    target: u64,
+) -> Option<Picked<'a>> {
+    let mut picked = Picked::new(utxos);
+    for index in by_amount(utxos) {
+        if picked.covers(target) {
+            break;
+        }
+        picked.push(parameters, index);
+    }
+
+    picked.covers(target).then_some(picked)
+}
+
+fn random_improve<'a>(
+    parameters: &ProtocolParameters,
+    utxos: &'a [(Utxo, Option<Output>)],
+    target: u64,
+) -> Result<Option<Picked<'a>>, SelectionError> {
+    let mut available = (0..utxos.len()).collect::<Vec<_>>();
+    let mut picked = Picked::new(utxos);
+
+    // phase 1: random selection until the target (and fee) is covered
+    while !picked.covers(target) {
+        if available.is_empty() {
+            return Ok(None);
+        }
+        picked.push(
+            parameters,
+            available.swap_remove(random_index(available.len())?),
+        );
+    }
+
+    // phase 2: improve the selection towards an ideal of twice the target,
+    // never going above three times the target. Each input grows the fee:
+    // the selection stops before the extra fee is not covered anymore
+    let ideal = target.saturating_mul(2);
+    let maximum = target.saturating_mul(3);
+    while !available.is_empty() {
+        let index = available.swap_remove(random_index(available.len())?);
+        let current = picked.value.coin;
+        let candidate = current.saturating_add(utxos[index].0.amount());
+        if candidate > maximum || candidate.abs_diff(ideal) >= current.abs_diff(ideal) {
+            break;
+        }
+
+        picked.push(parameters, index);
+        if !picked.covers(target) {
+            // the running totals cannot be taken back: start over from the
+            // inputs before this one
+            let inputs = &picked.inputs[..picked.inputs.len() - 1];
+            picked = Picked::with_inputs(parameters, utxos, inputs);
+            break;
+        }
+    }
+
+    Ok(Some(picked))
+}
+
+fn optimal<'a>(
+    parameters: &ProtocolParameters,
+    utxos: &'a [(Utxo, Option<Output>)],
+    target: u64,
+) -> Option<Picked<'a>> {
+    // the fewest inputs is given by the largest first selection
+    let fewest = largest_first(parameters, utxos, target)?;
+    let size = fewest.inputs.len();
+
+    let candidates = by_amount(utxos);
+    // prefix[n] is the lovelace of the n largest UTxOs
+    let prefix = std::iter::once(0)
+        .chain(candidates.iter().scan(0u64, |total, index| {
+            *total = total.saturating_add(utxos[*index].0.amount());
+            Some(*total)
+        }))
+        .collect();
+
+    // the fee of any transaction with that many inputs is between the one
+    // without witness nor change output and the one with a witness per
+    // input and all the assets of the wallet at the longest address
+    let mut all = Value::default();
+    let mut longest_address: &[u8] = &[];
+    for (utxo, output) in utxos {
+        match output {
+            Some(output) => {
+                all.add(&output.value);
+                if output.address.len() > longest_address.len() {
+                    longest_address = &output.address;
+                }
+            }
+            None => all.coin = all.coin.saturating_add(utxo.amount()),
+        }
+    }
+    let min_fee = parameters.fee(parameters.tx_size(size as u64, 0, PAYMENT_OUTPUT_SIZE));
+    let max_fee = parameters.fee(parameters.tx_size(
+        size as u64,
+        size as u64,
+        PAYMENT_OUTPUT_SIZE + output_size(longest_address, &all) as u64,
+    ));
+
+    // among the subsets of that many inputs, search the one with the
+    // least change: the fee is the same and less value is moved around
+    let mut search = Search {
+        parameters,
+        utxos,
+        target,
+        candidates,
+        prefix,
+        size,
+        min_fee,
+        max_fee,
+        current: Vec::with_capacity(size),
+        best_excess: fewest.value.coin - fewest.required(target),
+        best: fewest,
+        iterations: 0,
+    };
+    search.run(0, 0);
+
+    Some(search.best)
+}
+
+/// the depth first search of the optimal selection
+struct Search<'a, 'p> {
+    parameters: &'p ProtocolParameters,
+    utxos: &'a [(Utxo, Option<Output>)],
+    target: u64,
+    /// the UTxOs, largest first
+    candidates: Vec<usize>,
+    /// the lovelace of the first candidates, see [`optimal`]
+    prefix: Vec<u64>,
+    /// the number of inputs of the subsets
+    size: usize,
+    /// bounds on the fee of the subsets
+    min_fee: u64,
+    max_fee: u64,
+    current: Vec<usize>,
+    best: Picked<'a>,
+    /// the change of the best selection, in lovelace
+    best_excess: u64,
+    iterations: usize,
+}
+
+impl Search<'_, '_> {
+    /// search the subsets extending `current`, whose lovelace is `sum`,
+    /// with the candidates from `start`
+    fn run(&mut self, start: usize, sum: u64) {
+        self.iterations += 1;
+        if self.iterations > MAX_ITERATIONS || self.best_excess == 0 {
+            return;
+        }
+
+        if self.current.len() == self.size {
+            self.evaluate(sum);
+            return;
+        }
+
+        let remaining = self.size - self.current.len();
+        for position in start..self.candidates.len() {
+            // the candidates are sorted by decreasing amount: if the largest
+            // remaining ones cannot cover the target, no other subset can
+            if self.candidates.len() - position < remaining {
+                break;
+            }
+            let reachable =
+                sum.saturating_add(self.prefix[position + remaining] - self.prefix[position]);
+            if reachable < self.target.saturating_add(self.min_fee) {
+                break;
+            }
+
+            let index = self.candidates[position];
+            self.current.push(index);
+            self.run(
+                position + 1,
+                sum.saturating_add(self.utxos[index].0.amount()),
+            );
+            self.current.pop();
+        }
+    }
+
+    /// keep the subset if it covers its fee with less change than the best
+    ///
+    /// The fee is only estimated for the subsets the bounds on the fee do
+    /// not rule out.
+    fn evaluate(&mut self, sum: u64) {
+        let target = self.target;
+        // not enough for the least fee, or more change than the best even
+        // with the largest fee
+        if sum < target.saturating_add(self.min_fee)
+            || sum.saturating_sub(target.saturating_add(self.max_fee)) >= self.best_excess
+        {
+            return;
+        }
+
+        let picked = Picked::with_inputs(self.parameters, self.utxos, &self.current);
+        if picked.covers(target) && picked.value.coin - picked.required(target) < self.best_excess {
+            self.best_excess = picked.value.coin - picked.required(target);
+            self.best = picked;
+        }
+    }
+}
+
+fn random_index(len: usize) -> Result<usize, SelectionError> {
+    let random = getrandom::u32().map_err(|error| {
+        gloo::console::error!(error.to_string());
+        SelectionError::NoRandomness
+    })?;
+    Ok(random as usize % len)
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LargestFirst => f.write_str("Largest first"),
+            Self::RandomImprove => f.write_str("Random improve (CIP-2)"),
+            Self::Optimal => f.write_str("Fee aware optimal"),
+        }
+    }
+}
+
+impl fmt::Display for SelectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InsufficientFunds => {
+                f.write_str("not enough funds to pay the amount and the fee")
+            }
+            Self::ChangeBelowMinimumAda => {
+                f.write_str("the change would be below the minimum ADA of an output")
+            }
+            Self::NoRandomness => f.write_str("the browser did not provide random numbers"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::output::{decode_output, utxo_bytes};
+    use pallas_codec::minicbor;
+
+    const ALGORITHMS: [Algorithm; 3] = [
+        Algorithm::LargestFirst,
+        Algorithm::RandomImprove,
+        Algorithm::Optimal,
+    ];
+
+    /// ADA only UTxOs, each at an address of its own
+    fn utxos(amounts: &[u64]) -> Vec<(Utxo, Option<Output>)> {
+        amounts
+            .iter()
+            .enumerate()
+            .map(|(index, amount)| {
+                let address = [&[0x00][..], &[index as u8; 28][..], &[0x51; 28][..]].concat();
+                let value = Value {
+                    coin: *amount,
+                    ..Value::default()
+                };
+                let bytes = utxo_bytes(&[index as u8; 32], 0, &address, &value);
+                let utxo: Utxo = minicbor::decode(&bytes).expect("the UTxO should decode");
+                let output = decode_output(&utxo).expect("the output should decode");
+                (utxo, Some(output))
+            })
+            .collect()
+    }
+
+    /// the fee of a transaction spending the inputs, estimated from scratch
+    fn fee(
+        parameters: &ProtocolParameters,
+        utxos: &[(Utxo, Option<Output>)],
+        inputs: &[usize],
+    ) -> u64 {
+        let outputs = inputs
+            .iter()
+            .map(|index| utxos[*index].1.as_ref().expect("the outputs are decoded"))
+            .collect::<Vec<_>>();
+        let mut value = Value::default();
+        for output in &outputs {
+            value.add(&output.value);
+        }
+        let change_size = output_size(&outputs[0].address, &value) as u64;
+        parameters.fee(parameters.tx_size(
+            inputs.len() as u64,
+            inputs.len() as u64,
+            PAYMENT_OUTPUT_SIZE + change_size,
+        ))
+    }
+
+    #[test]
+    fn insufficient_funds() {
+        let parameters = ProtocolParameters::bundled();
+        let utxos = utxos(&[2_000_000, 3_000_000]);
+
+        for algorithm in ALGORITHMS {
+            for target in [10_000_000, 5_000_000] {
+                assert_eq!(
+                    select(algorithm, &parameters, &utxos, target),
+                    Err(SelectionError::InsufficientFunds),
+                    "{algorithm} paying {target}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn change_below_minimum_ada() {
+        let parameters = ProtocolParameters::bundled();
+        let utxos = utxos(&[10_000_000]);
+        let fee = fee(&parameters, &utxos, &[0]);
+
+        for algorithm in ALGORITHMS {
+            assert_eq!(
+                select(algorithm, &parameters, &utxos, 10_000_000 - fee - 100_000),
+                Err(SelectionError::ChangeBelowMinimumAda),
+                "{algorithm}"
+            );
+
+            // no change output at all
+            let selection = select(algorithm, &parameters, &utxos, 10_000_000 - fee)
+                .expect("the UTxO should pay the amount and the fee");
+            assert_eq!(selection.change, Value::default(), "{algorithm}");
+            assert_eq!(selection.fee, fee, "{algorithm}");
+        }
+    }
+
+    #[test]
+    fn fee_covered_by_each_input() {
+        let parameters = ProtocolParameters::bundled();
+        // 5 of these pay the target, a 6th one is needed for the fee
+        let utxos = utxos(&[2_000_000; 12]);
+        let target = 10_000_000;
+
+        for algorithm in ALGORITHMS {
+            // random improve picks a different selection each time
+            for _ in 0..20 {
+                let selection = select(algorithm, &parameters, &utxos, target)
+                    .expect("the UTxOs should pay the amount and the fee");
+                let inputs = selection.inputs.len();
+                let total = inputs as u64 * 2_000_000;
+
+                assert!(inputs >= 6, "{algorithm} picked {inputs} inputs");
+                assert_eq!(selection.fee, fee(&parameters, &utxos, &selection.inputs));
+                assert_eq!(total, target + selection.fee + selection.change.coin);
+                assert_eq!(selection.linked_addresses, inputs);
+                if algorithm != Algorithm::RandomImprove {
+                    assert_eq!(inputs, 6, "{algorithm}");
+                }
+            }
+        }
+    }
+}
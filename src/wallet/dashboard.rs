@@ -0,0 +1,209 @@
+//! the wallets connected together, side by side
+//!
+//! Each [`Wallet`] shares a [`WalletSummary`] of its scan through the
+//! context. When several extensions are connected the [`WalletsDashboard`]
+//! adds them up, per network, and points at the stake keys and addresses found in more
+//! than one extension: the same recovery phrase restored in two wallets,
+//! or funds sent between them.
+//!
+//! [`Wallet`]: super::Wallet
+
+use super::lovelace::{Lovelace, Symbol};
+use crate::context::ContextHandle;
+use cardano_connector::Address;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use yew::prelude::*;
+
+/// what a wallet shares with the dashboard
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletSummary {
+    pub name: String,
+    pub icon: String,
+    pub symbol: Symbol,
+    pub balance: Option<Lovelace>,
+    pub utxos: usize,
+    /// the number of findings of each check, `None` while they run
+    pub findings: Option<Vec<(&'static str, usize)>>,
+    pub reward_addresses: HashSet<Address>,
+    /// the used, unused and change addresses
+    pub addresses: HashSet<Address>,
+}
+
+impl WalletSummary {
+    fn total_findings(&self) -> Option<usize> {
+        self.findings
+            .as_ref()
+            .map(|findings| findings.iter().map(|(_, count)| count).sum())
+    }
+}
+
+/// the addresses reported by more than one wallet, with these wallets
+fn shared<'a>(
+    summaries: &'a [&'a WalletSummary],
+    addresses: impl Fn(&'a WalletSummary) -> &'a HashSet<Address>,
+) -> Vec<(&'a Address, BTreeSet<&'a str>)> {
+    let mut wallets: BTreeMap<String, (&Address, BTreeSet<&str>)> = BTreeMap::new();
+    for summary in summaries {
+        for address in addresses(summary) {
+            wallets
+                .entry(address.to_hex())
+                .or_insert_with(|| (address, BTreeSet::new()))
+                .1
+                .insert(&summary.name);
+        }
+    }
+
+    wallets
+        .into_values()
+        .filter(|(_, wallets)| wallets.len() > 1)
+        .collect()
+}
+
+#[function_component]
+pub fn WalletsDashboard() -> Html {
+    let ctx = use_context::<ContextHandle>().unwrap();
+    // a wallet disconnected while it was scanned may still share its scan
+    let summaries = ctx
+        .summaries
+        .values()
+        .filter(|summary| {
+            ctx.wallets
+                .iter()
+                .any(|wallet| *wallet.name() == summary.name)
+        })
+        .map(|summary| &**summary)
+        .collect::<Vec<_>>();
+
+    // mainnet and testnet ADA are not added up: one total per network
+    let mut symbols: Vec<Symbol> = Vec::new();
+    for summary in &summaries {
+        if !symbols.contains(&summary.symbol) {
+            symbols.push(summary.symbol);
+        }
+    }
+    let mixed_networks = symbols.len() > 1;
+    let on_network = |symbol: Symbol| {
+        summaries
+            .iter()
+            .filter(move |summary| summary.symbol == symbol)
+    };
+    let totals = symbols
+        .into_iter()
+        .map(|symbol| {
+            let balance: Option<Lovelace> = on_network(symbol).map(|summary| summary.balance).sum();
+            let utxos: usize = on_network(symbol).map(|summary| summary.utxos).sum();
+            let findings: Option<usize> = on_network(symbol)
+                .map(|summary| summary.total_findings())
+                .sum();
+            (symbol, balance, utxos, findings)
+        })
+        .collect::<Vec<_>>();
+
+    let shared_stake_keys = shared(&summaries, |summary| &summary.reward_addresses);
+    let shared_addresses = shared(&summaries, |summary| &summary.addresses);
+
+    let waiting = ctx.wallets.len().saturating_sub(summaries.len());
+
+    let view_shared = |title: &str, found: &[(&Address, BTreeSet<&str>)]| {
+        if found.is_empty() {
+            return html! {};
+        }
+        html! {
+            <div class="alert alert-warning">
+                <h6 class="alert-heading">{format!("{title} ({})", found.len())}</h6>
+                <ul class="mb-0 small">
+                    { for found.iter().map(|(address, wallets)| html! {
+                        <li>
+                            <span class="font-monospace text-break">
+                                {address.to_bech32().unwrap_or_else(|_| address.to_hex())}
+                            </span>
+                            {format!(" in {}", wallets.iter().copied().collect::<Vec<_>>().join(", "))}
+                        </li>
+                    }) }
+                </ul>
+            </div>
+        }
+    };
+
+    html! {
+        <div class="card mb-4">
+            <div class="card-header">
+                <h3 class="card-title mb-0">{format!("{} connected wallets", ctx.wallets.len())}</h3>
+            </div>
+            <div class="card-body">
+                if waiting > 0 {
+                    <p class="text-muted small">{format!("Waiting for {waiting} wallet(s) to be scanned.")}</p>
+                }
+                if mixed_networks {
+                    <div class="alert alert-info small">{"The wallets are not all on the same network, the totals are given per network."}</div>
+                }
+                <div class="table-responsive">
+                    <table class="table table-sm align-middle">
+                        <thead>
+                            <tr>
+                                <th>{"Wallet"}</th>
+                                <th class="text-end">{"Balance"}</th>
+                                <th class="text-end">{"UTxOs"}</th>
+                                <th>{"Findings"}</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            { for summaries.iter().map(|summary| html! {
+                                <tr>
+                                    <td>
+                                        <img src={summary.icon.clone()} alt="wallet icon" width="20" height="20" class="me-2" />
+                                        {&summary.name}
+                                    </td>
+                                    <td class="text-end">
+                                        {summary.balance.map(|balance| balance.display(summary.symbol).to_string()).unwrap_or_else(|| "?".to_owned())}
+                                    </td>
+                                    <td class="text-end">{summary.utxos}</td>
+                                    <td>{view_findings(summary)}</td>
+                                </tr>
+                            }) }
+                        </tbody>
+                        <tfoot>
+                            { for totals.iter().map(|(symbol, balance, utxos, findings)| html! {
+                                <tr class="fw-bold">
+                                    <td>{if mixed_networks { format!("Total ({symbol})") } else { "Total".to_owned() }}</td>
+                                    <td class="text-end">
+                                        {balance.map(|balance| balance.display(*symbol).to_string()).unwrap_or_else(|| "?".to_owned())}
+                                    </td>
+                                    <td class="text-end">{utxos}</td>
+                                    <td>{findings.map(|findings| findings.to_string()).unwrap_or_else(|| "…".to_owned())}</td>
+                                </tr>
+                            }) }
+                        </tfoot>
+                    </table>
+                </div>
+                {view_shared("Stake keys found in several wallets", &shared_stake_keys)}
+                {view_shared("Addresses found in several wallets", &shared_addresses)}
+            </div>
+        </div>
+    }
+}
+
+fn view_findings(summary: &WalletSummary) -> Html {
+    let Some(findings) = &summary.findings else {
+        return html! {
+            <div class="spinner-border spinner-border-sm text-primary" role="status">
+                <span class="visually-hidden">{"Loading..."}</span>
+            </div>
+        };
+    };
+    let found = findings
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .collect::<Vec<_>>();
+
+    if found.is_empty() {
+        return html! { <span class="badge text-bg-success">{"None"}</span> };
+    }
+    html! {
+        <div class="d-flex flex-wrap gap-1">
+            { for found.iter().map(|(label, count)| html! {
+                <span class="badge text-bg-warning">{format!("{label}: {count}")}</span>
+            }) }
+        </div>
+    }
+}
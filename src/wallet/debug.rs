@@ -0,0 +1,159 @@
+use super::{
+    cbor::{diagnose, Node, RawResponse},
+    dom_scope::DomScope,
+};
+use std::rc::Rc;
+use yew::prelude::*;
+
+/// list the raw CIP-30 responses received during the scan
+///
+/// The diagnostic tree is only rendered for the selected item: a wallet
+/// may return thousands of UTxOs.
+#[derive(Default)]
+pub struct DebugPanel {
+    /// the response and the item within the response
+    selected: Option<(usize, usize)>,
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    pub responses: Rc<Vec<RawResponse>>,
+}
+
+pub enum Message {
+    Select(usize, usize),
+}
+
+impl Component for DebugPanel {
+    type Message = Message;
+    type Properties = Properties;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self::default()
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        ctx.props() != old_props
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::Select(response, item) => {
+                self.selected = if self.selected == Some((response, item)) {
+                    None
+                } else {
+                    Some((response, item))
+                };
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let link = ctx.link();
+        let responses = &ctx.props().responses;
+        let scope = DomScope::of(ctx);
+
+        html! {
+            <div class="accordion my-4" id={scope.id("debugAccordion")}>
+                <div class="accordion-item">
+                    <h2 class="accordion-header">
+                        <button class="accordion-button collapsed" type="button" data-bs-toggle="collapse" data-bs-target={scope.target("rawResponses")}>
+                            {format!("Debug: raw CIP-30 responses ({})", responses.len())}
+                        </button>
+                    </h2>
+                    <div id={scope.id("rawResponses")} class="accordion-collapse collapse">
+                        <div class="accordion-body">
+                            <p class="small text-muted">
+                                {"The responses as the wallet returned them: the CBOR of the UTxOs and of the balance, the bytes of the addresses."}
+                            </p>
+                            { for responses.iter().enumerate().map(|(r, response)| html! {
+                                <details class="mb-2">
+                                    <summary>{format!("{} ({} items)", response.method, response.items.len())}</summary>
+                                    if let Some(error) = &response.error {
+                                        <div class="ms-3 small text-danger">{error.clone()}</div>
+                                    }
+                                    <ul class="list-unstyled ms-3">
+                                        { for response.items.iter().enumerate().map(|(i, item)| {
+                                            let selected = self.selected == Some((r, i));
+                                            html! {
+                                                <li>
+                                                    <span role="button"
+                                                        class={classes!("font-monospace", "small", selected.then_some("fw-bold"))}
+                                                        onclick={link.callback(move |_| Message::Select(r, i))}>
+                                                        {match &item.bytes {
+                                                            Some(bytes) => format!("#{i}: {} bytes {}", bytes.len(), abbreviate(&item.raw)),
+                                                            None => format!("#{i}: {}", abbreviate(&item.raw)),
+                                                        }}
+                                                    </span>
+                                                    if selected {
+                                                        <div class="row my-2">
+                                                            <div class="col-md-7">
+                                                                if let Some(bytes) = &item.bytes {
+                                                                    {view_tree(bytes)}
+                                                                }
+                                                            </div>
+                                                            <div class="col-md-5">
+                                                                <pre class="small">{item.interpretation.clone()}</pre>
+                                                            </div>
+                                                        </div>
+                                                    }
+                                                </li>
+                                            }
+                                        }) }
+                                    </ul>
+                                </details>
+                            }) }
+                        </div>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
+
+fn view_tree(bytes: &[u8]) -> Html {
+    match diagnose(bytes) {
+        Ok(node) => view_node(&node),
+        Err(error) => html! {
+            <>
+                <div class="small text-muted">{format!("Not a CBOR data item ({error}), raw bytes:")}</div>
+                <div class="font-monospace small text-break">{hex::encode(bytes)}</div>
+            </>
+        },
+    }
+}
+
+fn view_node(node: &Node) -> Html {
+    let label = format!(
+        "{:04x}..{:04x} {}",
+        node.offset,
+        node.offset + node.len,
+        node.header
+    );
+
+    if node.children.is_empty() {
+        html! { <div class="font-monospace small text-break">{label}</div> }
+    } else {
+        html! {
+            <details open=true>
+                <summary class="font-monospace small">{label}</summary>
+                <div class="ms-3">
+                    { for node.children.iter().map(view_node) }
+                </div>
+            </details>
+        }
+    }
+}
+
+/// the first and last characters, the items are not always hex
+fn abbreviate(raw: &str) -> String {
+    let len = raw.chars().count();
+    if len > 32 {
+        let start = raw.chars().take(16).collect::<String>();
+        let end = raw.chars().skip(len - 16).collect::<String>();
+        format!("{start}…{end}")
+    } else {
+        raw.to_owned()
+    }
+}
@@ -0,0 +1,629 @@
+//! mock CIP-30 wallets injected into `window.cardano` for demonstrations
+//!
+//! The demo mode is enabled by adding `?demo` to the URL, optionally with
+//! the wallets to inject: `?demo=healthy,dust`. The mock wallets answer
+//! with fixed responses so the application can be demonstrated without any
+//! wallet extension installed. `getUtxos` and `getUsedAddresses` answer the
+//! page asked for, if any.
+
+use super::{
+    cip30::INTERNAL_ERROR,
+    output::{utxo_bytes, value_bytes, AssetName, PolicyId, Value},
+};
+use js_sys::{Array, Object, Promise, Reflect};
+use pallas_addresses::ByronAddress;
+use std::{cell::Cell, future::Future, rc::Rc};
+use wasm_bindgen::{closure::Closure, JsValue};
+use wasm_bindgen_futures::future_to_promise;
+use web_sys::UrlSearchParams;
+
+/// the query parameter enabling the demo mode
+const DEMO_PARAMETER: &str = "demo";
+
+const TESTNET: u8 = 0;
+const MAINNET: u8 = 1;
+
+/// the stake key of the mock wallets
+const WALLET_STAKE_KEY: u8 = 0x51;
+
+/// a Byron address of the early days of mainnet
+const BYRON_ADDRESS: &str = "Ae2tdPwUPEZFRbyhz3cpfC2CumGzNkFBN2L42rcUc2yjQpEkxDbkPodpMAi";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scenario {
+    /// nothing to report
+    Healthy,
+    /// funds left on Byron addresses
+    ByronLeftovers,
+    /// addresses mixing the payment key of the wallet with foreign stake
+    /// keys, pointers or no stake key at all
+    FrankenAddresses,
+    /// many UTxOs of dust and airdropped tokens
+    DustHeavy,
+    /// slow responses and failing calls
+    Misbehaving,
+}
+
+/// the responses of a mock wallet
+struct MockWallet {
+    network_id: u8,
+    /// the CBOR of the `TransactionUnspentOutput`s
+    utxos: Vec<Vec<u8>>,
+    collateral: Vec<Vec<u8>>,
+    balance: Value,
+    change_address: Vec<u8>,
+    used_addresses: Vec<Vec<u8>>,
+    unused_addresses: Vec<Vec<u8>>,
+    reward_addresses: Vec<Vec<u8>>,
+    /// the methods failing with an internal error
+    failing: &'static [&'static str],
+    /// the methods answering late, with the delay in milliseconds
+    slow: &'static [(&'static str, u32)],
+}
+
+/// inject the mock wallets selected in the URL, if the demo mode is on
+pub fn install_demo_wallets() {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let Some(selection) = window
+        .location()
+        .search()
+        .ok()
+        .and_then(|search| UrlSearchParams::new_with_str(&search).ok())
+        .and_then(|parameters| parameters.get(DEMO_PARAMETER))
+    else {
+        return;
+    };
+
+    let scenarios = if selection.is_empty() {
+        Scenario::ALL.to_vec()
+    } else {
+        selection
+            .split(',')
+            .filter_map(|key| Scenario::from_key(key.trim()))
+            .collect()
+    };
+
+    let cardano = match Reflect::get(&window, &"cardano".into()) {
+        Ok(cardano) if cardano.is_object() => cardano,
+        _ => {
+            let cardano = JsValue::from(Object::new());
+            let _ = Reflect::set(&window, &"cardano".into(), &cardano);
+            cardano
+        }
+    };
+
+    for scenario in scenarios {
+        let wallet = scenario.wallet().into_js(scenario);
+        let _ = Reflect::set(&cardano, &scenario.injected_key().into(), &wallet);
+    }
+}
+
+impl Scenario {
+    pub const ALL: [Self; 5] = [
+        Self::Healthy,
+        Self::ByronLeftovers,
+        Self::FrankenAddresses,
+        Self::DustHeavy,
+        Self::Misbehaving,
+    ];
+
+    /// the name of the scenario in the `demo` query parameter
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::ByronLeftovers => "byron",
+            Self::FrankenAddresses => "franken",
+            Self::DustHeavy => "dust",
+            Self::Misbehaving => "misbehaving",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|scenario| scenario.key() == key)
+    }
+
+    /// the key of the wallet in `window.cardano`
+    fn injected_key(self) -> String {
+        format!("demo_{}", self.key())
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Healthy => "Demo: healthy",
+            Self::ByronLeftovers => "Demo: Byron leftovers",
+            Self::FrankenAddresses => "Demo: franken addresses",
+            Self::DustHeavy => "Demo: dust heavy",
+            Self::Misbehaving => "Demo: misbehaving API",
+        }
+    }
+
+    fn icon(self) -> String {
+        let letter = self.key()[..1].to_uppercase();
+        format!(
+            "data:image/svg+xml,%3Csvg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 32 32'%3E\
+             %3Crect width='32' height='32' rx='6' fill='%236c757d'/%3E\
+             %3Ctext x='16' y='22' font-size='18' text-anchor='middle' fill='white'%3E{letter}%3C/text%3E%3C/svg%3E"
+        )
+    }
+
+    fn wallet(self) -> MockWallet {
+        match self {
+            Self::Healthy => healthy(),
+            Self::ByronLeftovers => byron_leftovers(),
+            Self::FrankenAddresses => franken_addresses(),
+            Self::DustHeavy => dust_heavy(),
+            Self::Misbehaving => misbehaving(),
+        }
+    }
+}
+
+fn healthy() -> MockWallet {
+    let mut wallet = MockWallet::new(TESTNET);
+    wallet.add_utxo(
+        0x01,
+        0,
+        base(TESTNET, 0x11, WALLET_STAKE_KEY),
+        ada(152_350_000),
+    );
+    wallet.add_utxo(
+        0x02,
+        1,
+        base(TESTNET, 0x12, WALLET_STAKE_KEY),
+        ada(48_000_000),
+    );
+    wallet.add_collateral(0x03, 0, base(TESTNET, 0x13, WALLET_STAKE_KEY));
+    wallet.used_addresses = (0x11..=0x13)
+        .map(|key| base(TESTNET, key, WALLET_STAKE_KEY))
+        .collect();
+    wallet.unused_addresses = vec![base(TESTNET, 0x14, WALLET_STAKE_KEY)];
+    wallet.change_address = base(TESTNET, 0x14, WALLET_STAKE_KEY);
+    wallet
+}
+
+fn byron_leftovers() -> MockWallet {
+    let byron = ByronAddress::from_base58(BYRON_ADDRESS)
+        .expect("the demo Byron address should be valid")
+        .to_vec();
+
+    let mut wallet = MockWallet::new(MAINNET);
+    wallet.add_utxo(0x01, 0, byron.clone(), ada(1_250_000_000));
+    wallet.add_utxo(0x02, 3, byron.clone(), ada(3_400_000));
+    wallet.add_utxo(
+        0x03,
+        0,
+        base(MAINNET, 0x11, WALLET_STAKE_KEY),
+        ada(75_000_000),
+    );
+    wallet.add_collateral(0x04, 0, base(MAINNET, 0x12, WALLET_STAKE_KEY));
+    wallet.used_addresses = vec![
+        byron,
+        base(MAINNET, 0x11, WALLET_STAKE_KEY),
+        base(MAINNET, 0x12, WALLET_STAKE_KEY),
+    ];
+    wallet.change_address = base(MAINNET, 0x13, WALLET_STAKE_KEY);
+    wallet.unused_addresses = vec![wallet.change_address.clone()];
+    wallet
+}
+
+fn franken_addresses() -> MockWallet {
+    let foreign_stake_key = 0x66;
+    let addresses = [
+        base(TESTNET, 0x11, WALLET_STAKE_KEY),
+        base(TESTNET, 0x12, foreign_stake_key),
+        enterprise(TESTNET, 0x13),
+        pointer(TESTNET, 0x14, [0x81, 0x8f, 0x2a, 0x02, 0x00]),
+        script_base(TESTNET, 0x15, WALLET_STAKE_KEY),
+    ];
+
+    let mut wallet = MockWallet::new(TESTNET);
+    for (index, address) in addresses.iter().enumerate() {
+        wallet.add_utxo(0x01, index as u64, address.clone(), ada(12_000_000));
+    }
+    wallet.add_collateral(0x02, 0, base(TESTNET, 0x11, WALLET_STAKE_KEY));
+    wallet.used_addresses = addresses.to_vec();
+    wallet.change_address = base(TESTNET, 0x16, WALLET_STAKE_KEY);
+    wallet.unused_addresses = vec![wallet.change_address.clone()];
+    wallet
+}
+
+fn dust_heavy() -> MockWallet {
+    let mut wallet = MockWallet::new(TESTNET);
+    wallet.add_utxo(
+        0x01,
+        0,
+        base(TESTNET, 0x11, WALLET_STAKE_KEY),
+        ada(310_000_000),
+    );
+    wallet.add_collateral(0x02, 0, base(TESTNET, 0x11, WALLET_STAKE_KEY));
+
+    // small UTxOs at addresses the wallet does not list
+    for index in 0..40u8 {
+        let lovelace = 150_000 + u64::from(index) * 20_000;
+        wallet.add_utxo(
+            0x10 + index,
+            0,
+            enterprise(TESTNET, 0x80 + index),
+            ada(lovelace),
+        );
+    }
+
+    // unsolicited airdrops with the minimum ADA
+    let airdrops = [
+        "claim-your-reward.io",
+        "ADA Giveaway",
+        "\u{0410}DA Voucher",
+        "Free NFT visit site",
+    ];
+    for (index, name) in airdrops.into_iter().enumerate() {
+        let mut value = ada(1_168_010);
+        value
+            .assets
+            .entry(PolicyId::new(vec![0xd0 + index as u8; 28]))
+            .or_default()
+            .insert(AssetName::new(name.as_bytes().to_vec()), 1);
+        wallet.add_utxo(
+            0x60 + index as u8,
+            0,
+            base(TESTNET, 0x12, WALLET_STAKE_KEY),
+            value,
+        );
+    }
+
+    wallet.used_addresses = vec![
+        base(TESTNET, 0x11, WALLET_STAKE_KEY),
+        base(TESTNET, 0x12, WALLET_STAKE_KEY),
+    ];
+    wallet.change_address = base(TESTNET, 0x13, WALLET_STAKE_KEY);
+    wallet.unused_addresses = vec![wallet.change_address.clone()];
+    wallet
+}
+
+fn misbehaving() -> MockWallet {
+    let mut wallet = healthy();
+    wallet.failing = &["getCollateral", "getBalance"];
+    wallet.slow = &[("getUtxos", 3_000), ("getUsedAddresses", 1_500)];
+    wallet
+}
+
+impl MockWallet {
+    fn new(network_id: u8) -> Self {
+        Self {
+            network_id,
+            utxos: Vec::new(),
+            collateral: Vec::new(),
+            balance: Value::default(),
+            change_address: Vec::new(),
+            used_addresses: Vec::new(),
+            unused_addresses: Vec::new(),
+            reward_addresses: vec![reward(network_id, WALLET_STAKE_KEY)],
+            failing: &[],
+            slow: &[],
+        }
+    }
+
+    fn add_utxo(&mut self, transaction: u8, index: u64, address: Vec<u8>, value: Value) {
+        self.balance.add(&value);
+        self.utxos
+            .push(utxo_bytes(&[transaction; 32], index, &address, &value));
+    }
+
+    /// a UTxO of 5 ADA, also returned by `getCollateral`
+    fn add_collateral(&mut self, transaction: u8, index: u64, address: Vec<u8>) {
+        self.add_utxo(transaction, index, address, ada(5_000_000));
+        self.collateral.extend(self.utxos.last().cloned());
+    }
+
+    fn into_js(self, scenario: Scenario) -> JsValue {
+        let mock = Rc::new(self);
+        let enabled = Rc::new(Cell::new(false));
+
+        let api = Object::new();
+        let methods: [(&'static str, Answer); 8] = [
+            ("getNetworkId", |mock, _| Ok(JsValue::from(mock.network_id))),
+            ("getUtxos", |mock, [_, paginate]| {
+                page(&mock.utxos, paginate)
+            }),
+            ("getCollateral", |mock, _| Ok(hex_array(&mock.collateral))),
+            ("getBalance", |mock, _| {
+                Ok(JsValue::from(hex::encode(value_bytes(&mock.balance))))
+            }),
+            ("getChangeAddress", |mock, _| {
+                Ok(JsValue::from(hex::encode(&mock.change_address)))
+            }),
+            ("getUsedAddresses", |mock, [paginate, _]| {
+                page(&mock.used_addresses, paginate)
+            }),
+            ("getUnusedAddresses", |mock, _| {
+                Ok(hex_array(&mock.unused_addresses))
+            }),
+            ("getRewardAddresses", |mock, _| {
+                Ok(hex_array(&mock.reward_addresses))
+            }),
+        ];
+        for (method, response) in methods {
+            let mock = mock.clone();
+            let function = function(move |arguments| {
+                let mock = mock.clone();
+                async move { mock.answer(method, response, arguments).await }
+            });
+            let _ = Reflect::set(&api, &method.into(), &function);
+        }
+        let _ = Reflect::set(
+            &api,
+            &"getExtensions".into(),
+            &function(|_| async { Ok(Array::new().into()) }),
+        );
+        // some dApps look for `getCollateral` in the experimental namespace
+        let experimental = Object::new();
+        if let Ok(get_collateral) = Reflect::get(&api, &"getCollateral".into()) {
+            let _ = Reflect::set(&experimental, &"getCollateral".into(), &get_collateral);
+        }
+        let _ = Reflect::set(&api, &"experimental".into(), &experimental);
+
+        let wallet = Object::new();
+        let _ = Reflect::set(&wallet, &"name".into(), &scenario.name().into());
+        let _ = Reflect::set(&wallet, &"icon".into(), &scenario.icon().into());
+        let _ = Reflect::set(&wallet, &"apiVersion".into(), &"1.0.0".into());
+        let _ = Reflect::set(&wallet, &"supportedExtensions".into(), &Array::new());
+        let _ = Reflect::set(&wallet, &"isEnabled".into(), &{
+            let enabled = enabled.clone();
+            function(move |_| {
+                let enabled = enabled.get();
+                async move { Ok(JsValue::from(enabled)) }
+            })
+        });
+        let _ = Reflect::set(&wallet, &"enable".into(), &{
+            let api = JsValue::from(api);
+            function(move |_| {
+                enabled.set(true);
+                let api = api.clone();
+                async move { Ok(api) }
+            })
+        });
+
+        wallet.into()
+    }
+
+    async fn answer(
+        &self,
+        method: &str,
+        response: Answer,
+        arguments: [JsValue; 2],
+    ) -> Result<JsValue, JsValue> {
+        if let Some((_, delay)) = self.slow.iter().find(|(slow, _)| *slow == method) {
+            gloo::timers::future::TimeoutFuture::new(*delay).await;
+        }
+
+        if self.failing.contains(&method) {
+            let error = Object::new();
+            let _ = Reflect::set(&error, &"code".into(), &INTERNAL_ERROR.into());
+            let _ = Reflect::set(
+                &error,
+                &"info".into(),
+                &format!("{method} failed (demo wallet)").into(),
+            );
+            return Err(error.into());
+        }
+
+        response(self, &arguments)
+    }
+}
+
+/// the answer of a mock wallet to a method, given its arguments
+type Answer = fn(&MockWallet, &[JsValue; 2]) -> Result<JsValue, JsValue>;
+
+/// a JS function returning a promise of the future's result, given its
+/// first two arguments
+fn function<F, Fut>(f: F) -> JsValue
+where
+    F: Fn([JsValue; 2]) -> Fut + 'static,
+    Fut: Future<Output = Result<JsValue, JsValue>> + 'static,
+{
+    // the mock wallets live as long as the page: the closures are leaked
+    Closure::<dyn Fn(JsValue, JsValue) -> Promise>::new(move |first, second| {
+        future_to_promise(f([first, second]))
+    })
+    .into_js_value()
+}
+
+/// the page of the items asked for by the CIP-30 `Paginate`, all the
+/// items without it
+fn page(items: &[Vec<u8>], paginate: &JsValue) -> Result<JsValue, JsValue> {
+    let field = |name: &str| {
+        Reflect::get(paginate, &name.into())
+            .ok()
+            .and_then(|value| value.as_f64())
+            .map(|value| value as usize)
+    };
+    let (Some(page), Some(limit)) = (field("page"), field("limit")) else {
+        return Ok(hex_array(items));
+    };
+
+    let pages = items.len().div_ceil(limit.max(1));
+    if page > 0 && page >= pages {
+        let error = Object::new();
+        let _ = Reflect::set(&error, &"maxSize".into(), &(pages as u32).into());
+        return Err(error.into());
+    }
+    let start = (page * limit).min(items.len());
+    let end = (start + limit).min(items.len());
+    Ok(hex_array(&items[start..end]))
+}
+
+fn hex_array(items: &[Vec<u8>]) -> JsValue {
+    items
+        .iter()
+        .map(|item| JsValue::from(hex::encode(item)))
+        .collect::<Array>()
+        .into()
+}
+
+fn ada(lovelace: u64) -> Value {
+    Value {
+        coin: lovelace,
+        ..Value::default()
+    }
+}
+
+/// a base address, key payment and key stake
+fn base(network: u8, payment: u8, stake: u8) -> Vec<u8> {
+    [&[network][..], &[payment; 28][..], &[stake; 28][..]].concat()
+}
+
+/// a base address, script payment and key stake
+fn script_base(network: u8, payment: u8, stake: u8) -> Vec<u8> {
+    [&[0x10 | network][..], &[payment; 28][..], &[stake; 28][..]].concat()
+}
+
+fn pointer(network: u8, payment: u8, pointer: [u8; 5]) -> Vec<u8> {
+    [&[0x40 | network][..], &[payment; 28][..], &pointer[..]].concat()
+}
+
+fn enterprise(network: u8, payment: u8) -> Vec<u8> {
+    [&[0x60 | network][..], &[payment; 28][..]].concat()
+}
+
+fn reward(network: u8, stake: u8) -> Vec<u8> {
+    [&[0xe0 | network][..], &[stake; 28][..]].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::{output::decode_output, session::Response};
+    use cardano_connector::{Address, Utxo};
+    use pallas_codec::minicbor;
+
+    fn decode_utxos(items: &[Vec<u8>]) -> Vec<Utxo> {
+        items
+            .iter()
+            .map(|item| minicbor::decode(item).expect("the mock UTxOs should decode"))
+            .collect()
+    }
+
+    #[test]
+    fn mock_responses_decode() {
+        for scenario in Scenario::ALL {
+            let wallet = scenario.wallet();
+            let utxos = decode_utxos(&wallet.utxos);
+            for utxo in utxos.iter().chain(&decode_utxos(&wallet.collateral)) {
+                decode_output(utxo).expect("the mock outputs should decode");
+                utxo.address().expect("the mock addresses should decode");
+            }
+            assert!(decode_utxos(&wallet.collateral)
+                .iter()
+                .all(|collateral| utxos.contains(collateral)));
+
+            let balance = Response::Balance(hex::encode(value_bytes(&wallet.balance)));
+            let total: u64 = utxos.iter().map(Utxo::amount).sum();
+            assert_eq!(balance.to_balance(), Ok(total), "{scenario:?}");
+
+            for address in [&wallet.change_address]
+                .into_iter()
+                .chain(&wallet.used_addresses)
+                .chain(&wallet.unused_addresses)
+                .chain(&wallet.reward_addresses)
+            {
+                Response::Address(hex::encode(address))
+                    .to_address()
+                    .expect("the mock addresses should decode");
+            }
+        }
+    }
+
+    #[test]
+    fn byron_leftovers_hold_byron_utxos() {
+        let utxos = decode_utxos(&byron_leftovers().utxos);
+        let byron = utxos
+            .iter()
+            .filter(|utxo| matches!(utxo.address(), Ok(Address::Byron(..))))
+            .count();
+        assert_eq!(byron, 2);
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod browser_tests {
+    use super::*;
+    use crate::wallet::{
+        api::{Api, Backend, Call},
+        cancel::CancelToken,
+        cip30::WalletApi,
+        policy::ApiPolicy,
+    };
+    use std::cell::RefCell;
+    use wasm_bindgen_test::wasm_bindgen_test;
+    use yew::Callback;
+
+    /// a wallet with more UTxOs and used addresses than fit in a page
+    fn large() -> MockWallet {
+        let mut wallet = MockWallet::new(TESTNET);
+        for index in 0..250u64 {
+            wallet.add_utxo(
+                index as u8,
+                index,
+                base(TESTNET, 0x11, WALLET_STAKE_KEY),
+                ada(1_500_000),
+            );
+        }
+        wallet.used_addresses = (0..200u8)
+            .map(|key| base(TESTNET, key, WALLET_STAKE_KEY))
+            .collect();
+        wallet
+    }
+
+    #[wasm_bindgen_test]
+    async fn paged_responses() {
+        let window = web_sys::window().expect("the tests run in a browser");
+        let cardano = Object::new();
+        let _ = Reflect::set(
+            &cardano,
+            &"demo_healthy".into(),
+            &large().into_js(Scenario::Healthy),
+        );
+        let _ = Reflect::set(&window, &"cardano".into(), &cardano);
+
+        let wallet = cardano_connector::wallets()
+            .into_iter()
+            .find(|wallet| &*wallet.name() == Scenario::Healthy.name())
+            .expect("the connector should list the mock wallet");
+        let api = WalletApi::enable(&wallet)
+            .await
+            .expect("the mock wallet should enable");
+        let calls = Rc::new(RefCell::new(Vec::<Call>::new()));
+        let api = Api::new(
+            Backend::Live(api),
+            "test",
+            Callback::from({
+                let calls = calls.clone();
+                move |call| calls.borrow_mut().push(call)
+            }),
+            ApiPolicy::default(),
+            CancelToken::default(),
+        );
+
+        let utxos = api.all_utxos().await.expect("the UTxOs should be listed");
+        assert_eq!(utxos.to_utxos().map(|utxos| utxos.len()), Ok(250));
+        let used = api
+            .used_addresses()
+            .await
+            .expect("the used addresses should be listed");
+        assert_eq!(used.to_addresses().map(|used| used.len()), Ok(200));
+
+        // 3 pages of UTxOs, the last one short; 2 pages of addresses and
+        // the `PaginateError` of the third
+        let count = |method: &str| {
+            calls
+                .borrow()
+                .iter()
+                .filter(|call| call.method == method)
+                .count()
+        };
+        assert_eq!(count("getUtxos"), 3);
+        assert_eq!(count("getUsedAddresses"), 3);
+
+        let _ = Reflect::delete_property(&window, &"cardano".into());
+    }
+}
@@ -0,0 +1,46 @@
+//! the ids of the elements of a wallet's panels
+//!
+//! Several wallets can be displayed on the same page: the ids of their
+//! collapsible panels and inputs are prefixed with the wallet so each
+//! button toggles the panel of its own wallet.
+
+use std::rc::Rc;
+use yew::prelude::*;
+
+/// provided by the [`Wallet`] to the components it renders
+///
+/// [`Wallet`]: super::Wallet
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DomScope(Rc<str>);
+
+impl DomScope {
+    pub fn new(name: &str) -> Self {
+        let prefix: String = name
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        Self(prefix.into())
+    }
+
+    /// the scope of the component, none outside of a wallet
+    pub fn of<C: Component>(ctx: &Context<C>) -> Self {
+        ctx.link()
+            .context::<Self>(Callback::noop())
+            .map(|(scope, _)| scope)
+            .unwrap_or_default()
+    }
+
+    pub fn id(&self, name: &str) -> String {
+        if self.0.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{}-{name}", self.0)
+        }
+    }
+
+    /// the selector of the element with the given id, for `data-bs-target`
+    pub fn target(&self, name: &str) -> String {
+        format!("#{}", self.id(name))
+    }
+}
@@ -0,0 +1,25 @@
+//! let the user download the data collected by the analyser
+
+use gloo::file::{Blob, ObjectUrl};
+use wasm_bindgen::JsCast;
+use web_sys::HtmlAnchorElement;
+
+/// offer the contents as a file to download
+///
+/// The download may not have started when this returns: the URL is only
+/// released once the returned value is dropped.
+pub fn download(filename: &str, mime_type: &str, contents: &str) -> Option<ObjectUrl> {
+    let url = ObjectUrl::from(Blob::new_with_options(contents, Some(mime_type)));
+
+    let anchor = web_sys::window()?
+        .document()?
+        .create_element("a")
+        .ok()?
+        .dyn_into::<HtmlAnchorElement>()
+        .ok()?;
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+
+    Some(url)
+}
@@ -0,0 +1,115 @@
+//! estimate how fragmented the UTxO set is and whether the whole balance
+//! can be spent in a single transaction
+
+use super::{
+    lovelace::LOVELACE_PER_ADA,
+    output::{output_size, Output, Value},
+    protocol_parameters::ProtocolParameters,
+};
+use cardano_connector::Utxo;
+use std::collections::HashSet;
+
+/// upper bounds, in ADA, of the UTxO sizes histogram buckets. The labels
+/// are in ADA too, the currency symbol is added when displayed.
+const BUCKETS: &[(u64, &str)] = &[
+    (1, "< 1"),
+    (10, "1 - 10"),
+    (100, "10 - 100"),
+    (1_000, "100 - 1k"),
+    (10_000, "1k - 10k"),
+    (100_000, "10k - 100k"),
+    (u64::MAX, "≥ 100k"),
+];
+
+pub struct Fragmentation {
+    /// 0 when the whole balance is in a single UTxO, close to 100 when it
+    /// is spread evenly over many UTxOs
+    pub score: u8,
+    /// number of UTxOs per size bucket
+    pub histogram: Vec<(&'static str, usize)>,
+    pub spend_all: SpendAll,
+}
+
+/// estimate of a transaction spending every UTxO to a single output
+pub struct SpendAll {
+    pub tx_size: u64,
+    pub fee: u64,
+    /// number of transactions needed to spend every UTxO
+    pub num_transactions: u64,
+}
+
+pub fn fragmentation(
+    parameters: &ProtocolParameters,
+    utxos: &[(Utxo, Option<Output>)],
+) -> Fragmentation {
+    let total: u128 = utxos.iter().map(|(utxo, _)| utxo.amount() as u128).sum();
+    let sum_of_squares: u128 = utxos
+        .iter()
+        .map(|(utxo, _)| (utxo.amount() as u128).pow(2))
+        .sum();
+
+    // 1 - Herfindahl index of the UTxO amounts
+    let score = if total == 0 {
+        0
+    } else {
+        100 - (sum_of_squares * 100 / total.pow(2)) as u8
+    };
+
+    let mut histogram = BUCKETS
+        .iter()
+        .map(|(_, label)| (*label, 0))
+        .collect::<Vec<_>>();
+    for (utxo, _) in utxos {
+        let ada = utxo.amount() / LOVELACE_PER_ADA;
+        let bucket = BUCKETS
+            .iter()
+            .position(|(bound, _)| ada < *bound)
+            .unwrap_or(BUCKETS.len() - 1);
+        histogram[bucket].1 += 1;
+    }
+
+    Fragmentation {
+        score,
+        histogram,
+        spend_all: spend_all(parameters, utxos),
+    }
+}
+
+fn spend_all(parameters: &ProtocolParameters, utxos: &[(Utxo, Option<Output>)]) -> SpendAll {
+    let outputs = utxos
+        .iter()
+        .filter_map(|(_, output)| output.as_ref())
+        .collect::<Vec<_>>();
+
+    let mut value = Value::default();
+    for output in &outputs {
+        value.add(&output.value);
+    }
+    let address = outputs
+        .first()
+        .map(|output| output.address.as_slice())
+        .unwrap_or_default();
+    let output = output_size(address, &value) as u64;
+
+    // one witness per distinct address, as an approximation of the
+    // number of distinct payment keys
+    let num_witnesses = outputs
+        .iter()
+        .map(|output| output.address.as_slice())
+        .collect::<HashSet<_>>()
+        .len() as u64;
+    let num_inputs = utxos.len() as u64;
+
+    let tx_size = parameters.tx_size(num_inputs, num_witnesses, output);
+    let overhead = parameters.tx_size(0, 0, output);
+    let inputs_size = tx_size - overhead;
+
+    let capacity = parameters.max_tx_size.saturating_sub(overhead).max(1);
+    let num_transactions = inputs_size.div_ceil(capacity).max(1);
+
+    SpendAll {
+        tx_size,
+        fee: parameters.fee(tx_size),
+        num_transactions,
+    }
+}
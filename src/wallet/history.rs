@@ -0,0 +1,443 @@
+//! the scans of the wallets, kept in the browser between two visits
+//!
+//! Each completed scan of a wallet extension is stored in IndexedDB with
+//! the findings of the checks, keyed by the name of the wallet and its
+//! first reward address (the account). The [`ScanHistory`] panel lists the
+//! previous scans of the account and shows what changed since one of them.
+
+use super::{
+    dom_scope::DomScope,
+    lovelace::{Lovelace, Symbol},
+    snapshot::{SnapshotRecord, WalletSnapshot},
+    utxo_table::utxo_id,
+};
+use js_sys::{Array, Promise, JSON};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, fmt, rc::Rc};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{
+    IdbDatabase, IdbObjectStoreParameters, IdbOpenDbRequest, IdbRequest, IdbTransactionMode,
+};
+use yew::prelude::*;
+
+const DATABASE: &str = "cardano-web-wallet-analyser";
+const DATABASE_VERSION: u32 = 1;
+const SCANS: &str = "scans";
+/// index of the scans on their wallet and account
+const BY_ACCOUNT: &str = "by_account";
+
+/// a completed scan, as stored in the history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanRecord {
+    /// the name of the wallet extension
+    pub wallet: String,
+    /// hex of the first reward address, empty if the wallet has none
+    pub account: String,
+    /// milliseconds since the epoch
+    pub timestamp: f64,
+    pub snapshot: SnapshotRecord,
+    /// the findings of the checks, e.g. `Byron address: <tx>#<ix>`
+    pub findings: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryError(String);
+
+/// what changed between two scans of the same account
+pub struct ScanDiff {
+    pub added: Vec<String>,
+    pub spent: Vec<String>,
+    pub previous_balance: Option<Lovelace>,
+    pub balance: Option<Lovelace>,
+    pub appeared: Vec<String>,
+    pub resolved: Vec<String>,
+}
+
+impl ScanRecord {
+    pub fn new(wallet: String, snapshot: &WalletSnapshot, findings: BTreeSet<String>) -> Self {
+        Self {
+            wallet,
+            account: snapshot
+                .reward_addresses
+                .first()
+                .map(|address| address.to_hex())
+                .unwrap_or_default(),
+            timestamp: js_sys::Date::now(),
+            snapshot: snapshot.record(),
+            findings,
+        }
+    }
+
+    fn date(&self) -> String {
+        js_sys::Date::new(&JsValue::from_f64(self.timestamp))
+            .to_locale_string("default", &JsValue::UNDEFINED)
+            .into()
+    }
+}
+
+/// a record is only equal to itself: comparing the snapshots is costly
+impl PartialEq for ScanRecord {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl ScanDiff {
+    pub fn between(previous: &ScanRecord, current: &ScanRecord) -> Result<Self, String> {
+        let previous_snapshot = WalletSnapshot::from_record(previous.snapshot.clone())?;
+        let snapshot = WalletSnapshot::from_record(current.snapshot.clone())?;
+
+        let previous_utxos: BTreeSet<_> = previous_snapshot.utxos.iter().map(utxo_id).collect();
+        let utxos: BTreeSet<_> = snapshot.utxos.iter().map(utxo_id).collect();
+
+        Ok(Self {
+            added: utxos.difference(&previous_utxos).cloned().collect(),
+            spent: previous_utxos.difference(&utxos).cloned().collect(),
+            previous_balance: previous_snapshot.balance.ok(),
+            balance: snapshot.balance.ok(),
+            appeared: current
+                .findings
+                .difference(&previous.findings)
+                .cloned()
+                .collect(),
+            resolved: previous
+                .findings
+                .difference(&current.findings)
+                .cloned()
+                .collect(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.spent.is_empty()
+            && self.previous_balance == self.balance
+            && self.appeared.is_empty()
+            && self.resolved.is_empty()
+    }
+}
+
+/// save the scan in the history
+pub async fn save(scan: &ScanRecord) -> Result<(), HistoryError> {
+    let json = serde_json::to_string(scan).map_err(|error| HistoryError(error.to_string()))?;
+    let value = JSON::parse(&json)?;
+
+    let database = open().await?;
+    let store = database
+        .transaction_with_str_and_mode(SCANS, IdbTransactionMode::Readwrite)?
+        .object_store(SCANS)?;
+    complete(&store.add(&value)?).await?;
+    Ok(())
+}
+
+/// the scans of the account, oldest first
+pub async fn scans(wallet: &str, account: &str) -> Result<Vec<ScanRecord>, HistoryError> {
+    let database = open().await?;
+    let index = database
+        .transaction_with_str(SCANS)?
+        .object_store(SCANS)?
+        .index(BY_ACCOUNT)?;
+    let key = Array::of2(&JsValue::from_str(wallet), &JsValue::from_str(account));
+    let found: Array = complete(&index.get_all_with_key(&key)?)
+        .await?
+        .unchecked_into();
+
+    let mut scans = found
+        .iter()
+        .filter_map(|value| {
+            let json = String::from(JSON::stringify(&value).ok()?);
+            serde_json::from_str::<ScanRecord>(&json)
+                .map_err(|error| gloo::console::error!(error.to_string()))
+                .ok()
+        })
+        .collect::<Vec<_>>();
+    scans.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+    Ok(scans)
+}
+
+async fn open() -> Result<IdbDatabase, HistoryError> {
+    let factory = web_sys::window()
+        .and_then(|window| window.indexed_db().ok().flatten())
+        .ok_or_else(|| HistoryError("IndexedDB is not available".to_owned()))?;
+    let request = factory.open_with_u32(DATABASE, DATABASE_VERSION)?;
+
+    let upgrading = request.clone();
+    let on_upgrade_needed = Closure::once_into_js(move |_: web_sys::Event| {
+        if let Err(error) = create_stores(&upgrading) {
+            gloo::console::error!(error.to_string());
+        }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade_needed.unchecked_ref()));
+
+    Ok(complete(&request).await?.unchecked_into())
+}
+
+/// the first version of the database, or an upgrade from an older one
+fn create_stores(request: &IdbOpenDbRequest) -> Result<(), HistoryError> {
+    let database: IdbDatabase = request.result()?.unchecked_into();
+    if database.object_store_names().contains(SCANS) {
+        return Ok(());
+    }
+
+    let parameters = IdbObjectStoreParameters::new();
+    parameters.set_auto_increment(true);
+    let store = database.create_object_store_with_optional_parameters(SCANS, &parameters)?;
+    store.create_index_with_str_sequence(
+        BY_ACCOUNT,
+        &Array::of2(&JsValue::from_str("wallet"), &JsValue::from_str("account")),
+    )?;
+    Ok(())
+}
+
+/// wait for the request to succeed, with its result
+async fn complete(request: &IdbRequest) -> Result<JsValue, HistoryError> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+
+    if JsFuture::from(promise).await.is_err() {
+        let message = request
+            .error()
+            .ok()
+            .flatten()
+            .map(|error| error.message())
+            .unwrap_or_else(|| "the request failed".to_owned());
+        return Err(HistoryError(message));
+    }
+    Ok(request.result()?)
+}
+
+impl From<JsValue> for HistoryError {
+    fn from(error: JsValue) -> Self {
+        Self(
+            error
+                .dyn_ref::<js_sys::Error>()
+                .map(|error| String::from(error.message()))
+                .unwrap_or_else(|| format!("{error:?}")),
+        )
+    }
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "scan history: {}", self.0)
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ScanHistoryProperties {
+    /// the scan just completed, once saved
+    pub scan: Rc<ScanRecord>,
+    #[prop_or_default]
+    pub symbol: Symbol,
+}
+
+/// the previous scans of the account and what changed since one of them
+#[function_component]
+pub fn ScanHistory(props: &ScanHistoryProperties) -> Html {
+    let scans = use_state(|| Option::<Rc<Result<Vec<ScanRecord>, HistoryError>>>::None);
+    // the scan to compare with, by default the one before the current scan
+    let selected = use_state(|| Option::<usize>::None);
+
+    {
+        let scans = scans.clone();
+        let selected = selected.clone();
+        use_effect_with(props.scan.clone(), move |scan| {
+            let scan = scan.clone();
+            spawn_local(async move {
+                let found = self::scans(&scan.wallet, &scan.account).await;
+                // the current scan is the last one saved
+                let previous = found
+                    .as_ref()
+                    .ok()
+                    .and_then(|found| found.len().checked_sub(2));
+                selected.set(previous);
+                scans.set(Some(Rc::new(found)));
+            });
+        });
+    }
+
+    // comparing decodes both snapshots: only done when the selection changes
+    let diff = use_memo(
+        (*selected, props.scan.clone(), (*scans).clone()),
+        |(selected, scan, scans)| {
+            let found = scans.as_deref()?.as_ref().ok()?;
+            let previous = found.get((*selected)?)?;
+            Some(ScanDiff::between(previous, scan))
+        },
+    );
+
+    let scope = use_context::<DomScope>().unwrap_or_default();
+    let symbol = props.symbol;
+    let content = match scans.as_deref() {
+        None => html! { <span class="text-muted">{"Loading the history..."}</span> },
+        Some(Err(error)) => html! {
+            <div class="alert alert-warning mb-0">{error.to_string()}</div>
+        },
+        Some(Ok(found)) if found.len() < 2 => html! {
+            <span class="text-muted">{"This is the first scan of this account, the next scans will be compared with it."}</span>
+        },
+        Some(Ok(found)) => {
+            let previous = selected.and_then(|index| found.get(index));
+
+            html! {
+                <div class="row">
+                    <div class="col-md-4">
+                        <div class="list-group list-group-flush small">
+                            { for found.iter().enumerate().rev().skip(1).map(|(index, scan)| {
+                                let selected = selected.clone();
+                                let active = *selected == Some(index);
+                                html! {
+                                    <button type="button"
+                                        class={classes!("list-group-item", "list-group-item-action", active.then_some("active"))}
+                                        onclick={move |_| selected.set(Some(index))}>
+                                        {scan.date()}
+                                        <span class="badge text-bg-secondary ms-2">{format!("{} findings", scan.findings.len())}</span>
+                                    </button>
+                                }
+                            }) }
+                        </div>
+                    </div>
+                    <div class="col-md-8">
+                        { match (previous, diff.as_ref()) {
+                            (Some(previous), Some(Ok(diff))) => view_diff(previous, diff, symbol),
+                            (_, Some(Err(error))) => html! {
+                                <div class="alert alert-warning">{format!("The scans cannot be compared: {error}")}</div>
+                            },
+                            _ => html! {},
+                        } }
+                    </div>
+                </div>
+            }
+        }
+    };
+
+    html! {
+        <div class="accordion my-4" id={scope.id("scanHistoryAccordion")}>
+            <div class="accordion-item">
+                <h2 class="accordion-header">
+                    <button class="accordion-button collapsed" type="button" data-bs-toggle="collapse" data-bs-target={scope.target("scanHistory")}>
+                        {"Scan history"}
+                    </button>
+                </h2>
+                <div id={scope.id("scanHistory")} class="accordion-collapse collapse">
+                    <div class="accordion-body">
+                        {content}
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+fn view_diff(previous: &ScanRecord, diff: &ScanDiff, symbol: Symbol) -> Html {
+    if diff.is_empty() {
+        return html! {
+            <p class="text-muted">{format!("Nothing changed since {}.", previous.date())}</p>
+        };
+    }
+
+    let balance = match (diff.previous_balance, diff.balance) {
+        (Some(previous), Some(balance)) if previous != balance => {
+            let change = match balance.checked_sub(previous) {
+                Some(gain) => format!("+{}", gain.display(symbol)),
+                None => format!(
+                    "-{}",
+                    previous
+                        .checked_sub(balance)
+                        .unwrap_or_default()
+                        .display(symbol)
+                ),
+            };
+            html! {
+                <p>
+                    {format!("Balance: {} → {} ", previous.display(symbol), balance.display(symbol))}
+                    <span class="badge text-bg-info">{change}</span>
+                </p>
+            }
+        }
+        _ => html! {},
+    };
+
+    let list = |title: &str, class: &'static str, items: &[String]| {
+        if items.is_empty() {
+            return html! {};
+        }
+        html! {
+            <>
+                <h6 class="mt-3">{format!("{title} ({})", items.len())}</h6>
+                <ul class="list-unstyled small font-monospace mb-0">
+                    { for items.iter().map(|item| html! { <li class={class}>{item}</li> }) }
+                </ul>
+            </>
+        }
+    };
+
+    html! {
+        <>
+            <p class="text-muted small">{format!("Changes since {}", previous.date())}</p>
+            {balance}
+            {list("UTxOs added", "text-success", &diff.added)}
+            {list("UTxOs spent", "text-danger", &diff.spent)}
+            {list("Findings appeared", "text-warning", &diff.appeared)}
+            {list("Findings resolved", "text-success", &diff.resolved)}
+        </>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::{session::Response, snapshot::byron_leftovers};
+
+    fn scan(snapshot: &WalletSnapshot, findings: &[&str]) -> ScanRecord {
+        ScanRecord {
+            wallet: "test".to_owned(),
+            account: String::new(),
+            timestamp: 0.0,
+            snapshot: snapshot.record(),
+            findings: findings.iter().map(|finding| finding.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn same_scan() {
+        let snapshot = WalletSnapshot::replay(&byron_leftovers());
+        let previous = scan(&snapshot, &["Byron address: a#0"]);
+        let current = scan(&snapshot, &["Byron address: a#0"]);
+
+        let diff = ScanDiff::between(&previous, &current).unwrap();
+
+        assert!(diff.is_empty());
+        assert!(diff.balance.is_some());
+    }
+
+    #[test]
+    fn spent_utxo_and_changed_finding() {
+        let mut session = byron_leftovers();
+        let previous_snapshot = WalletSnapshot::replay(&session);
+        let Some(Response::Utxos(utxos)) = session.responses.get_mut("getUtxos") else {
+            panic!("the fixture should record the UTxOs");
+        };
+        utxos.pop();
+        let snapshot = WalletSnapshot::replay(&session);
+
+        let previous = scan(
+            &previous_snapshot,
+            &["Byron address: a#0", "Used change address"],
+        );
+        let current = scan(&snapshot, &["Byron address: a#0", "Spam token: b#1"]);
+
+        let diff = ScanDiff::between(&previous, &current).unwrap();
+
+        let removed = previous_snapshot.utxos.last().unwrap();
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.spent, [utxo_id(removed)]);
+        assert_eq!(diff.previous_balance, diff.balance);
+        assert_eq!(diff.appeared, ["Spam token: b#1"]);
+        assert_eq!(diff.resolved, ["Used change address"]);
+        assert!(!diff.is_empty());
+    }
+}
@@ -0,0 +1,322 @@
+//! exact amounts of lovelace and their formatting as ADA
+//!
+//! Amounts are never converted to floating points: `u64` lovelace don't fit
+//! in the 53 bits mantissa of a `f64` and large balances would be rounded.
+
+use super::network_id::NetworkId;
+use pallas_addresses::Network;
+use serde::{Deserialize, Serialize};
+use std::{fmt, iter::Sum, str::FromStr, sync::OnceLock};
+
+pub const LOVELACE_PER_ADA: u64 = 1_000_000;
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct Lovelace(u64);
+
+/// the currency symbol to display, depends on the network of the wallet
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Symbol {
+    #[default]
+    Ada,
+    TestAda,
+}
+
+/// the lovelace formatted as ADA with the separators of the user's locale
+pub struct Display {
+    lovelace: Lovelace,
+    symbol: Symbol,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLovelaceError;
+
+/// the digit group and decimal separators of a locale
+struct Separators {
+    group: &'static str,
+    decimal: &'static str,
+}
+
+impl Lovelace {
+    pub const ZERO: Self = Self(0);
+
+    pub const fn new(lovelace: u64) -> Self {
+        Self(lovelace)
+    }
+
+    pub const fn as_u64(self) -> u64 {
+        self.0
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    pub fn saturating_add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+
+    /// format the amount as ADA followed by the currency symbol
+    pub fn display(self, symbol: Symbol) -> Display {
+        Display {
+            lovelace: self,
+            symbol,
+        }
+    }
+}
+
+/// the total of amounts above the ADA supply, only found in forged
+/// responses: the overflow is reported and the total stops at `u64::MAX`
+/// instead of wrapping around
+impl Sum for Lovelace {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        let mut total = Self::ZERO;
+        for lovelace in iter {
+            match total.checked_add(lovelace) {
+                Some(sum) => total = sum,
+                None => {
+                    gloo::console::error!("the total of the amounts overflows a u64 of lovelace");
+                    return Self(u64::MAX);
+                }
+            }
+        }
+        total
+    }
+}
+
+/// parse a decimal amount of ADA (e.g. `12.5`) into lovelace
+impl FromStr for Lovelace {
+    type Err = ParseLovelaceError;
+
+    fn from_str(amount: &str) -> Result<Self, Self::Err> {
+        let amount = amount.trim();
+        let (integer, decimals) = amount.split_once('.').unwrap_or((amount, ""));
+
+        let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+        if (integer.is_empty() && decimals.is_empty())
+            || decimals.len() > 6
+            || !is_digits(integer)
+            || !is_digits(decimals)
+        {
+            return Err(ParseLovelaceError);
+        }
+
+        let integer: u64 = if integer.is_empty() {
+            0
+        } else {
+            integer.parse().map_err(|_| ParseLovelaceError)?
+        };
+        let decimals: u64 = format!("{decimals:0<6}")
+            .parse()
+            .map_err(|_| ParseLovelaceError)?;
+
+        integer
+            .checked_mul(LOVELACE_PER_ADA)
+            .and_then(|lovelace| lovelace.checked_add(decimals))
+            .map(Self)
+            .ok_or(ParseLovelaceError)
+    }
+}
+
+impl From<NetworkId> for Symbol {
+    fn from(network_id: NetworkId) -> Self {
+        if network_id == NetworkId::MAINNET {
+            Self::Ada
+        } else {
+            Self::TestAda
+        }
+    }
+}
+
+/// the network tag of an address, when `getNetworkId` failed
+impl From<Network> for Symbol {
+    fn from(network: Network) -> Self {
+        match network {
+            Network::Mainnet => Self::Ada,
+            Network::Testnet | Network::Other(_) => Self::TestAda,
+        }
+    }
+}
+
+impl Separators {
+    /// the separators of the browser's preferred language
+    fn current() -> &'static Self {
+        static SEPARATORS: OnceLock<Separators> = OnceLock::new();
+
+        SEPARATORS.get_or_init(|| {
+            let language = web_sys::window()
+                .and_then(|window| window.navigator().language())
+                .unwrap_or_default();
+            Self::for_language(&language)
+        })
+    }
+
+    fn for_language(language: &str) -> Self {
+        const NARROW_NO_BREAK_SPACE: &str = "\u{202F}";
+
+        let mut parts = language.split(['-', '_']);
+        let language = parts.next().unwrap_or_default().to_lowercase();
+        let region = parts.next().unwrap_or_default().to_uppercase();
+
+        match (language.as_str(), region.as_str()) {
+            ("de" | "it" | "fr", "CH") => Self {
+                group: "’",
+                decimal: ".",
+            },
+            (
+                "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" | "hr" | "sl"
+                | "sr" | "vi",
+                _,
+            ) => Self {
+                group: ".",
+                decimal: ",",
+            },
+            (
+                "fr" | "ru" | "pl" | "cs" | "sk" | "uk" | "sv" | "nb" | "no" | "fi" | "hu" | "bg"
+                | "lt" | "lv" | "et",
+                _,
+            ) => Self {
+                group: NARROW_NO_BREAK_SPACE,
+                decimal: ",",
+            },
+            _ => Self {
+                group: ",",
+                decimal: ".",
+            },
+        }
+    }
+}
+
+impl Display {
+    fn write(&self, f: &mut impl fmt::Write, separators: &Separators) -> fmt::Result {
+        let integer = (self.lovelace.0 / LOVELACE_PER_ADA).to_string();
+        let decimals = self.lovelace.0 % LOVELACE_PER_ADA;
+
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                f.write_str(separators.group)?;
+            }
+            write!(f, "{digit}")?;
+        }
+        write!(f, "{}{decimals:06} {}", separators.decimal, self.symbol)
+    }
+}
+
+impl fmt::Display for Display {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, Separators::current())
+    }
+}
+
+impl fmt::Display for Lovelace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} lovelace", self.0)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ada => f.write_str("₳"),
+            Self::TestAda => f.write_str("tADA"),
+        }
+    }
+}
+
+impl fmt::Display for ParseLovelaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("not a valid ADA amount")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(amount: &str) -> Result<u64, ParseLovelaceError> {
+        amount.parse().map(Lovelace::as_u64)
+    }
+
+    /// the amount as displayed to a user of the language
+    fn display(lovelace: u64, symbol: Symbol, language: &str) -> String {
+        let mut displayed = String::new();
+        Lovelace::new(lovelace)
+            .display(symbol)
+            .write(&mut displayed, &Separators::for_language(language))
+            .expect("writing into a String is infallible");
+        displayed
+    }
+
+    #[test]
+    fn parse_decimal_amounts() {
+        assert_eq!(parse("12.5"), Ok(12_500_000));
+        assert_eq!(parse(".5"), Ok(500_000));
+        assert_eq!(parse("1."), Ok(1_000_000));
+        assert_eq!(parse(" 42 "), Ok(42_000_000));
+        assert_eq!(parse("0.000001"), Ok(1));
+    }
+
+    #[test]
+    fn parse_invalid_amounts() {
+        for amount in ["", ".", "1.2345678", "1,5", "abc", "-1", "1.2.3", "1e6"] {
+            assert_eq!(parse(amount), Err(ParseLovelaceError), "{amount:?}");
+        }
+    }
+
+    #[test]
+    fn parse_overflow() {
+        assert_eq!(parse("18446744073709.551615"), Ok(u64::MAX));
+        for amount in [
+            "18446744073709.551616",
+            "18446744073710",
+            "99999999999999999999",
+        ] {
+            assert_eq!(parse(amount), Err(ParseLovelaceError), "{amount:?}");
+        }
+    }
+
+    #[test]
+    fn digit_grouping() {
+        let lovelace = 1_234_567_890_000;
+        assert_eq!(
+            display(lovelace, Symbol::Ada, "en-US"),
+            "1,234,567.890000 ₳"
+        );
+        assert_eq!(
+            display(lovelace, Symbol::Ada, "de-DE"),
+            "1.234.567,890000 ₳"
+        );
+        assert_eq!(
+            display(lovelace, Symbol::Ada, "fr-FR"),
+            "1\u{202F}234\u{202F}567,890000 ₳"
+        );
+        assert_eq!(
+            display(lovelace, Symbol::Ada, "de-CH"),
+            "1’234’567.890000 ₳"
+        );
+        assert_eq!(display(lovelace, Symbol::Ada, ""), "1,234,567.890000 ₳");
+    }
+
+    #[test]
+    fn zero_padded_decimals() {
+        assert_eq!(display(1_000_005, Symbol::Ada, "en-US"), "1.000005 ₳");
+        assert_eq!(display(5, Symbol::Ada, "en-US"), "0.000005 ₳");
+        assert_eq!(display(0, Symbol::Ada, "de-DE"), "0,000000 ₳");
+        assert_eq!(display(999_000_000, Symbol::Ada, "en-US"), "999.000000 ₳");
+    }
+
+    #[test]
+    fn test_ada_symbol() {
+        assert_eq!(
+            display(2_500_000, Symbol::TestAda, "en-US"),
+            "2.500000 tADA"
+        );
+        assert_eq!(Symbol::from(NetworkId::TESTNET), Symbol::TestAda);
+        assert_eq!(Symbol::from(NetworkId::MAINNET), Symbol::Ada);
+    }
+}
@@ -1,28 +1,118 @@
+mod analysis;
+mod api;
+mod api_activity;
 mod balance;
+mod cancel;
+mod cbor;
+mod cip30;
+mod coin_selection;
+mod dashboard;
+mod debug;
+mod demo;
+mod dom_scope;
+mod export;
+mod fragmentation;
+mod history;
+mod lovelace;
 mod network_id;
+mod output;
+mod policy;
+mod protocol_parameters;
+mod session;
+mod simulator;
+mod snapshot;
 mod tasks;
+mod token_lists;
+mod utxo_table;
 mod utxos;
 mod views;
+mod watcher;
 
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeSet, HashMap},
+    rc::Rc,
+};
 
+pub use self::{
+    analysis::Analyser,
+    cancel::CancelToken,
+    dashboard::{WalletSummary, WalletsDashboard},
+    demo::install_demo_wallets,
+    session::Session,
+};
 use self::{
+    analysis::{analyse, AnalysisEvent, AnalysisProgress, CheckStatus},
+    api::{Api, ApiError, Backend, Call},
+    api_activity::ApiActivity,
+    balance::WalletBalance,
+    cancel::deadline,
+    cbor::RawResponse,
+    cip30::WalletApi,
+    debug::DebugPanel,
+    dom_scope::DomScope,
+    history::{ScanHistory, ScanRecord},
+    lovelace::Symbol,
     network_id::WalletNetworkId,
-    tasks::{analyse_wallet, AlertMessage},
-    utxos::UtxosView,
-    views::{ByronAddressView, InconsistentRewardAddressesView, UsedAddressesView},
+    output::Output,
+    policy::ApiPolicy,
+    session::WalletInfo,
+    snapshot::WalletSnapshot,
+    tasks::{
+        accounts::AccountsReport, change_address::ChangeAddressReport,
+        collateral::CollateralReport, spam::SuspiciousUtxo, wallet_details, AlertMessage, Check,
+    },
+    utxo_table::{utxo_id, UtxoFindings},
+    utxos::{DecodedUtxos, UtxosView},
+    views::{
+        AccountsView, ByronAddressView, ChangeAddressView, CollateralView, DatumsAndScriptsView,
+        EnterpriseAddressesView, InconsistentRewardAddressesView, PointerAddressesView,
+        ScriptCredentialsView, SpamTokensView, UsedAddressesView,
+    },
+    watcher::{watch, WalletChange},
 };
-use cardano_connector::{Address, ConnectedWallet, Utxo};
+use crate::context::{Action, ContextHandle};
+use cardano_connector::{Address, Utxo};
 use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
 
 #[derive(Default)]
 pub struct Wallet {
     state: State,
+    /// the currency symbol of the wallet's network
+    symbol: Symbol,
 
     utxos_with_byron_addresses: Option<HashMap<Address, Vec<Utxo>>>,
     utxos_with_unknown_reward_addresses: Option<HashMap<Address, Vec<Utxo>>>,
     utxos_with_used_addresses: Option<HashMap<Address, Vec<Utxo>>>,
+    utxos_with_pointer_addresses: Option<HashMap<Address, Vec<Utxo>>>,
+    utxos_with_enterprise_addresses: Option<HashMap<Address, Vec<Utxo>>>,
+    utxos_with_script_credentials: Option<HashMap<Address, Vec<Utxo>>>,
+    suspicious_utxos: Option<Vec<SuspiciousUtxo>>,
+    utxos_with_datums_or_scripts: Option<Vec<(Utxo, Output)>>,
+    collateral: Option<CollateralReport>,
+    accounts: Option<AccountsReport>,
+    change_address: Option<ChangeAddressReport>,
+    /// what the wallet reported during the last scan, `None` while scanning
+    snapshot: Option<Rc<WalletSnapshot>>,
+    /// the outputs of the UTxOs of the snapshot, decoded by the worker
+    decoded: Option<Rc<DecodedUtxos>>,
+    /// the raw responses of the wallet, for the debug panel
+    raw_responses: Rc<Vec<RawResponse>>,
+    /// the calls made to the wallet, for the API activity panel
+    api_calls: Rc<Vec<Call>>,
+    /// cancelled when the scan is abandoned, e.g. the wallet is deselected,
+    /// the wallet is watched for account changes until then
+    scan: CancelToken,
+    /// the wallet switched account or network, it was scanned again
+    notice: Option<WalletChange>,
+    /// the progress of the checks running in the worker
+    checks: Rc<Vec<(Check, CheckStatus)>>,
+    /// cancelled when the checks are run again, terminates the worker
+    analysis: CancelToken,
+    /// the scan of a wallet extension is saved in the history once analysed
+    unsaved: bool,
+    /// the scan, as saved in the history
+    scan_record: Option<Rc<ScanRecord>>,
 }
 
 #[derive(Default)]
@@ -30,20 +120,55 @@ enum State {
     #[default]
     NotConnected,
     Connecting,
-    Connected(ConnectedWallet),
-    Error(String),
+    Connected(Backend),
+    Error(ApiError),
 }
 
 pub enum WalletMessage {
     Connect,
-    Connected(ConnectedWallet),
-    ConnectionFailed(String),
+    Connected(Backend),
+    ConnectionFailed(ApiError),
+    Scanned(Rc<WalletSnapshot>),
+    Analyse,
+    Analysis(AnalysisEvent),
+    Saved(Rc<ScanRecord>),
+    WalletChanged(WalletChange),
+    DismissNotice,
     Alert(AlertMessage),
+    ApiCall(Call),
+    /// abandon the calls still waiting for the wallet
+    Cancel,
 }
 
 #[derive(Properties, PartialEq)]
 pub struct WalletProperties {
-    pub wallet: cardano_connector::Wallet,
+    pub source: Source,
+    /// cancelled by the context when the wallet is deselected, the scans
+    /// are cancelled with it
+    #[prop_or_default]
+    pub connection: CancelToken,
+}
+
+/// the wallet to analyse
+#[derive(Clone, PartialEq)]
+pub enum Source {
+    /// a wallet extension installed in the browser
+    Extension(cardano_connector::Wallet),
+    /// a session recorded from a wallet extension
+    Replay(Rc<Session>),
+}
+
+impl Source {
+    fn info(&self) -> WalletInfo {
+        match self {
+            Self::Extension(wallet) => WalletInfo {
+                name: wallet.name().to_string(),
+                icon: wallet.icon().to_string(),
+                version: wallet.version().to_string(),
+            },
+            Self::Replay(session) => session.wallet.clone(),
+        }
+    }
 }
 
 impl Component for Wallet {
@@ -51,21 +176,7 @@ impl Component for Wallet {
     type Properties = WalletProperties;
 
     fn create(ctx: &Context<Self>) -> Self {
-        let wallet = ctx.props().wallet.clone();
-        let link = ctx.link().clone();
-
-        // check that the wallet is already connected with our app
-        // before asking to go any further. This is because we do
-        // not want to ask the user to connect to our dapp already
-        // otherwise that would be a poor UX as we would require the
-        // user to do something with the wallet extension before we
-        // even finished loading the page.
-        spawn_local(async move {
-            if wallet.enabled().await.unwrap_or(false) {
-                link.send_message(WalletMessage::Connect);
-            }
-        });
-
+        Self::reconnect(ctx);
         Self::default()
     }
 
@@ -73,37 +184,184 @@ impl Component for Wallet {
         let link = ctx.link().clone();
         match msg {
             WalletMessage::Connect => {
-                let wallet = ctx.props().wallet.clone();
+                // a new scan replaces the one still running, if any
+                self.scan.cancel();
+                self.analysis.cancel();
+                let scan = ctx.props().connection.child();
 
-                spawn_local(async move {
-                    match wallet.enable().await {
-                        Ok(api) => link.send_message(WalletMessage::Connected(api)),
-                        Err(error) => {
-                            link.send_message(WalletMessage::ConnectionFailed(error.to_string()))
-                        }
+                match ctx.props().source.clone() {
+                    Source::Extension(wallet) => {
+                        let token = scan.clone();
+                        spawn_local(async move {
+                            let timeout = ApiPolicy::load().timeout("enable");
+                            let enable = WalletApi::enable(&wallet);
+                            let message = match deadline(enable, timeout, &token).await {
+                                Ok(Ok(api)) => WalletMessage::Connected(Backend::Live(api)),
+                                Ok(Err(error)) => WalletMessage::ConnectionFailed(
+                                    ApiError::Enable(error.to_string()),
+                                ),
+                                Err(interrupted) => WalletMessage::ConnectionFailed(
+                                    ApiError::interrupted(interrupted, "enable", timeout),
+                                ),
+                            };
+                            link.send_message(message);
+                        })
                     }
-                });
+                    Source::Replay(session) => {
+                        link.send_message(WalletMessage::Connected(Backend::Replay(session)))
+                    }
+                }
 
                 let old_state = std::mem::replace(&mut self.state, State::Connecting);
                 *self = Self {
                     state: State::Connecting,
+                    scan,
+                    notice: self.notice.take(),
                     ..Default::default()
                 };
 
                 !matches!(old_state, State::Connecting)
             }
             WalletMessage::Connected(cip30_api) => {
-                spawn_local(analyse_wallet(cip30_api.clone(), move |message| {
-                    link.send_message(WalletMessage::Alert(message));
-                }));
+                if self.scan.is_cancelled() {
+                    // the wallet answered after the scan was cancelled
+                    self.state = State::Error(ApiError::Cancelled);
+                    return true;
+                }
+                let scan = std::mem::take(&mut self.scan);
+                let api = Api::new(
+                    cip30_api.clone(),
+                    "WalletSnapshot",
+                    link.callback(WalletMessage::ApiCall),
+                    ApiPolicy::load(),
+                    scan.clone(),
+                );
+                spawn_local(async move {
+                    match WalletSnapshot::fetch(&api).await {
+                        Ok(snapshot) => {
+                            link.send_message(WalletMessage::Scanned(Rc::new(snapshot)))
+                        }
+                        Err(error) => link
+                            .send_message(WalletMessage::Alert(AlertMessage::APIError { error })),
+                    }
+                });
 
                 *self = Self {
                     state: State::Connected(cip30_api),
+                    scan,
+                    notice: self.notice.take(),
+                    ..Default::default()
+                };
+                true
+            }
+            WalletMessage::Scanned(snapshot) => {
+                self.raw_responses = Rc::new(snapshot.raw_responses());
+                self.snapshot = Some(snapshot);
+                // the replayed sessions are not kept in the history
+                self.unsaved = matches!(self.state, State::Connected(Backend::Live(_)));
+                self.watch(ctx);
+                link.send_message(WalletMessage::Analyse);
+                true
+            }
+            WalletMessage::Analyse => {
+                let Some(snapshot) = self.snapshot.clone() else {
+                    return false;
+                };
+
+                // the checks run again over the same snapshot, the wallet
+                // is not queried again
+                self.analysis.cancel();
+                let analysis = ctx.props().connection.child();
+                *self = Self {
+                    state: std::mem::take(&mut self.state),
+                    symbol: self.symbol,
+                    snapshot: Some(snapshot.clone()),
+                    // the same snapshot decodes to the same outputs
+                    decoded: self.decoded.take(),
+                    raw_responses: std::mem::take(&mut self.raw_responses),
+                    api_calls: std::mem::take(&mut self.api_calls),
+                    scan: std::mem::take(&mut self.scan),
+                    notice: self.notice.take(),
+                    checks: Rc::new(
+                        Check::ALL
+                            .into_iter()
+                            .map(|check| (check, CheckStatus::Pending))
+                            .collect(),
+                    ),
+                    analysis: analysis.clone(),
+                    unsaved: self.unsaved,
+                    scan_record: self.scan_record.take(),
                     ..Default::default()
                 };
+
+                {
+                    let link = link.clone();
+                    wallet_details(&snapshot, move |message| {
+                        link.send_message(WalletMessage::Alert(message));
+                    });
+                }
+                spawn_local(async move {
+                    analyse(&snapshot, &analysis, move |event| {
+                        link.send_message(WalletMessage::Analysis(event));
+                    })
+                    .await;
+                });
                 true
             }
+            WalletMessage::Analysis(event) => {
+                match event {
+                    AnalysisEvent::Decoded(decoded) => self.decoded = Some(Rc::new(decoded)),
+                    AnalysisEvent::Started(check) => self.set_status(check, CheckStatus::Running),
+                    AnalysisEvent::Finished(check, finding) => {
+                        self.set_status(check, CheckStatus::Done);
+                        let alert = self
+                            .snapshot
+                            .as_ref()
+                            .and_then(|snapshot| finding.into_alert(check, snapshot));
+                        if let Some(alert) = alert {
+                            self.update(ctx, WalletMessage::Alert(alert));
+                        }
+                    }
+                    AnalysisEvent::Failed(check) => self.set_status(check, CheckStatus::Failed),
+                    AnalysisEvent::Invalid(error) => {
+                        gloo::console::error!(error);
+                        // the page decoded the snapshot, the UTxOs are
+                        // still shown
+                        if let Some(snapshot) = &self.snapshot {
+                            self.decoded = Some(Rc::new(DecodedUtxos::new(&snapshot.utxos)));
+                        }
+                        for (_, status) in Rc::make_mut(&mut self.checks) {
+                            *status = CheckStatus::Failed;
+                        }
+                    }
+                }
+                if self.analysed() {
+                    self.share_summary(ctx, true);
+                    // the findings of a failed check are missing, the next
+                    // scans would report them as resolved
+                    if self.unsaved && !self.failed() {
+                        self.unsaved = false;
+                        self.save_scan(ctx);
+                    }
+                }
+                true
+            }
+            WalletMessage::Saved(scan) => {
+                self.scan_record = Some(scan);
+                true
+            }
+            WalletMessage::WalletChanged(change) => {
+                // the results are about the previous account, scan again
+                self.notice = Some(change);
+                link.send_message(WalletMessage::Connect);
+                true
+            }
+            WalletMessage::DismissNotice => self.notice.take().is_some(),
             WalletMessage::ConnectionFailed(error) => {
+                if matches!(error, ApiError::Cancelled) && !self.scan.is_cancelled() {
+                    // a previous scan, replaced by the current one
+                    return false;
+                }
                 *self = Self {
                     state: State::Error(error),
                     ..Default::default()
@@ -111,7 +369,11 @@ impl Component for Wallet {
                 true
             }
             WalletMessage::Alert(AlertMessage::APIError { error }) => {
-                self.state = State::Error(error.to_string());
+                if matches!(error, ApiError::Cancelled) && !self.scan.is_cancelled() {
+                    return false;
+                }
+                // the calls made so far stay in the API activity panel
+                self.state = State::Error(error);
                 true
             }
             WalletMessage::Alert(AlertMessage::UTxOsWithByronAddress { utxos }) => {
@@ -126,14 +388,89 @@ impl Component for Wallet {
                 self.utxos_with_used_addresses = Some(utxos);
                 true
             }
+            WalletMessage::Alert(AlertMessage::UTxOsWithPointerAddresses { utxos }) => {
+                self.utxos_with_pointer_addresses = Some(utxos);
+                true
+            }
+            WalletMessage::Alert(AlertMessage::UTxOsWithEnterpriseAddresses { utxos }) => {
+                self.utxos_with_enterprise_addresses = Some(utxos);
+                true
+            }
+            WalletMessage::Alert(AlertMessage::UTxOsWithScriptCredentials { utxos }) => {
+                self.utxos_with_script_credentials = Some(utxos);
+                true
+            }
+            WalletMessage::Alert(AlertMessage::SuspiciousUTxOs { utxos }) => {
+                self.suspicious_utxos = Some(utxos);
+                true
+            }
+            WalletMessage::Alert(AlertMessage::UTxOsWithDatumsOrScripts { utxos }) => {
+                self.utxos_with_datums_or_scripts = Some(utxos);
+                true
+            }
+            WalletMessage::Alert(AlertMessage::Collateral { report }) => {
+                self.collateral = Some(report);
+                true
+            }
+            WalletMessage::Alert(AlertMessage::Accounts { report }) => {
+                self.accounts = Some(report);
+                true
+            }
+            WalletMessage::Alert(AlertMessage::ChangeAddress { report }) => {
+                self.change_address = Some(report);
+                true
+            }
+            WalletMessage::Alert(AlertMessage::Network { symbol }) => {
+                self.symbol = symbol;
+                true
+            }
+            WalletMessage::Alert(AlertMessage::WalletAddresses { .. }) => {
+                // the addresses are shared before the checks are done, for
+                // the address inspector
+                self.share_summary(ctx, false);
+                false
+            }
+            WalletMessage::ApiCall(call) => {
+                Rc::make_mut(&mut self.api_calls).push(call);
+                true
+            }
+            WalletMessage::Cancel => {
+                self.scan.cancel();
+                false
+            }
         }
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         let link = ctx.link().clone();
-        let wallet_app_icon = ctx.props().wallet.icon().clone();
-        let wallet_app_name = ctx.props().wallet.name().clone();
-        let wallet_app_version = ctx.props().wallet.version().clone();
+        let source = &ctx.props().source;
+        let info = source.info();
+        let scope = DomScope::new(&info.name);
+
+        let cancel = {
+            let link = link.clone();
+            html! {
+                <button onclick={move |_| link.send_message(WalletMessage::Cancel)} class="btn btn-sm btn-outline-secondary ms-3">{"Cancel scan"}</button>
+            }
+        };
+
+        let notice = match self.notice {
+            Some(change) => {
+                let message = match change {
+                    WalletChange::Event => "The wallet switched to another account or network.",
+                    WalletChange::Network => "The wallet switched to another network.",
+                    WalletChange::Account => "The wallet switched to another account.",
+                };
+                let dismiss = link.callback(|_| WalletMessage::DismissNotice);
+                html! {
+                    <div class="alert alert-info alert-dismissible" role="alert">
+                        {message}{" The previous results were discarded and the wallet scanned again."}
+                        <button type="button" class="btn-close" aria-label="Close" onclick={dismiss}></button>
+                    </div>
+                }
+            }
+            None => html! {},
+        };
 
         let body_content = match &self.state {
             State::NotConnected => {
@@ -148,78 +485,363 @@ impl Component for Wallet {
                             <span class="visually-hidden">{"Loading..."}</span>
                         </div>
                         <span>{"Connecting..."}</span>
+                        {cancel}
                     </div>
                 }
             }
-            State::Connected(wallet) => {
+            State::Connected(_) => {
+                let on_token_lists_changed = link.callback(|()| WalletMessage::Analyse);
+                let symbol = self.symbol;
+
                 html! {
                     <>
-                        <UtxosView wallet={wallet.clone()} />
+                        if self.snapshot.is_none() {
+                            <div class="d-flex align-items-center justify-content-end">
+                                <span class="small text-muted">{"Waiting for the wallet..."}</span>
+                                {cancel}
+                            </div>
+                        }
+                        if let Some(snapshot) = &self.snapshot {
+                            <WalletBalance snapshot={snapshot.clone()} {symbol} />
+                        }
+                        <UtxosView snapshot={self.snapshot.clone()} decoded={self.decoded.clone()} findings={Rc::new(self.utxo_findings())} {symbol} />
 
                         <div class="d-flex justify-content-between align-items-center my-4">
                             <h4>{"Wallet Overview"}</h4>
                         </div>
 
+                        <AnalysisProgress checks={self.checks.clone()} />
                         <ByronAddressView utxos={self.utxos_with_byron_addresses.clone()} />
                         <InconsistentRewardAddressesView utxos={self.utxos_with_unknown_reward_addresses.clone()} />
+                        <AccountsView report={self.accounts.clone()} {symbol} />
                         <UsedAddressesView utxos={self.utxos_with_used_addresses.clone()} />
+                        <ChangeAddressView report={self.change_address.clone()} {symbol} />
+                        <PointerAddressesView utxos={self.utxos_with_pointer_addresses.clone()} {symbol} />
+                        <EnterpriseAddressesView utxos={self.utxos_with_enterprise_addresses.clone()} {symbol} />
+                        <ScriptCredentialsView utxos={self.utxos_with_script_credentials.clone()} {symbol} />
+                        <SpamTokensView utxos={self.suspicious_utxos.clone()} {on_token_lists_changed} {symbol} />
+                        <DatumsAndScriptsView utxos={self.utxos_with_datums_or_scripts.clone()} {symbol} />
+                        <CollateralView report={self.collateral.clone()} {symbol} />
+
+                        if let Some(scan) = &self.scan_record {
+                            <ScanHistory scan={scan.clone()} {symbol} />
+                        }
+                        <ApiActivity calls={self.api_calls.clone()} wallet={info.clone()} />
+                        <DebugPanel responses={self.raw_responses.clone()} />
                     </>
                 }
             }
             State::Error(error) => {
+                let (class, message, retry) = match error {
+                    ApiError::Cancelled => ("alert-info", "Scan cancelled.".to_owned(), "Scan Again"),
+                    ApiError::Timeout { .. } => (
+                        "alert-warning",
+                        format!("{error}. Check that the wallet is not waiting for an approval in its popup, or raise the timeout in the API activity panel."),
+                        "Try Again",
+                    ),
+                    error => ("alert-danger", format!("Connection error: {error}"), "Try Again"),
+                };
+
                 html! {
                     <>
-                        <div class="alert alert-danger" role="alert">
-                            {format!("Connection error: {}", error)}
+                        <div class={classes!("alert", class)} role="alert">
+                            {message}
                         </div>
-                        <button onclick={move |_| link.send_message(WalletMessage::Connect)} class="btn btn-primary">{"Try Again"}</button>
+                        <button onclick={move |_| link.send_message(WalletMessage::Connect)} class="btn btn-primary">{retry}</button>
+                        <ApiActivity calls={self.api_calls.clone()} wallet={info.clone()} />
                     </>
                 }
             }
         };
 
-        let network_id = if let State::Connected(wallet) = &self.state {
-            html! { <WalletNetworkId wallet={wallet.clone()} /> }
+        let network_id = if matches!(self.state, State::Connected(_)) {
+            html! { <WalletNetworkId snapshot={self.snapshot.clone()} /> }
         } else {
             html! {}
         };
 
         html! {
-            <div class="card">
+            <ContextProvider<DomScope> context={scope}>
+            <div class="card mb-4">
                 <div class="card-header">
                     <div class="d-flex align-items-center">
-                        <img src={wallet_app_icon} alt="wallet icon" width="32" height="32" class="me-2"/>
+                        <img src={info.icon} alt="wallet icon" width="32" height="32" class="me-2"/>
                         <h3 class="card-title mb-0 me-2">
-                            {wallet_app_name}
+                            {info.name}
                         </h3>
-                        <span class="badge rounded-pill text-bg-secondary">{wallet_app_version}</span>
+                        <span class="badge rounded-pill text-bg-secondary">{info.version}</span>
+                        if let Source::Replay(session) = source {
+                            <span class="badge rounded-pill text-bg-info ms-2">
+                                {if session.redacted { "replay, redacted" } else { "replay" }}
+                            </span>
+                        }
                         {network_id}
+                        if let Some(Ok(extensions)) = self.snapshot.as_ref().map(|snapshot| &snapshot.extensions) {
+                            { for extensions.iter().map(|cip| html! {
+                                <span class="badge rounded-pill text-bg-light me-1" title="extension enabled by the wallet">
+                                    {format!("CIP-{cip}")}
+                                </span>
+                            }) }
+                        }
                     </div>
                 </div>
                 <div class="card-body">
+                    {notice}
                     {body_content}
                 </div>
             </div>
+            </ContextProvider<DomScope>>
         }
     }
 
     fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
-        let new_wallet_app_icon = ctx.props().wallet.icon().clone();
-        let old_wallet_app_icon = old_props.wallet.icon().clone();
+        let changed = match (&ctx.props().source, &old_props.source) {
+            (Source::Extension(new), Source::Extension(old)) => new.name() != old.name(),
+            (new, old) => new != old,
+        };
 
-        if new_wallet_app_icon != old_wallet_app_icon {
+        if changed {
+            self.scan.cancel();
+            self.analysis.cancel();
             *self = Self::default();
+            Self::reconnect(ctx);
+        }
+        changed
+    }
 
-            let wallet = ctx.props().wallet.clone();
-            let link = ctx.link().clone();
-            spawn_local(async move {
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        // the wallet was deselected, nobody is waiting for the scan anymore
+        self.scan.cancel();
+        self.analysis.cancel();
+    }
+}
+
+impl Wallet {
+    /// connect to the wallet if it is already connected with our app
+    ///
+    /// A recorded session is replayed straight away.
+    fn reconnect(ctx: &Context<Self>) {
+        let link = ctx.link().clone();
+
+        match ctx.props().source.clone() {
+            // check that the wallet is already connected with our app
+            // before asking to go any further. This is because we do
+            // not want to ask the user to connect to our dapp already
+            // otherwise that would be a poor UX as we would require the
+            // user to do something with the wallet extension before we
+            // even finished loading the page.
+            Source::Extension(wallet) => spawn_local(async move {
                 if wallet.enabled().await.unwrap_or(false) {
                     link.send_message(WalletMessage::Connect);
                 }
+            }),
+            Source::Replay(_) => link.send_message(WalletMessage::Connect),
+        }
+    }
+
+    /// scan again when the wallet switches account or network
+    fn watch(&self, ctx: &Context<Self>) {
+        let (State::Connected(Backend::Live(wallet)), Some(snapshot)) =
+            (&self.state, &self.snapshot)
+        else {
+            return;
+        };
+
+        // the polls are not recorded in the API activity panel
+        let api = Api::new(
+            Backend::Live(wallet.clone()),
+            "WalletWatcher",
+            Callback::noop(),
+            ApiPolicy::load(),
+            self.scan.clone(),
+        );
+        let (wallet, snapshot, token) = (wallet.clone(), snapshot.clone(), self.scan.clone());
+        let link = ctx.link().clone();
+
+        spawn_local(async move {
+            if let Ok(change) = watch(&wallet, &api, &snapshot, &token).await {
+                link.send_message(WalletMessage::WalletChanged(change));
+            }
+        });
+    }
+
+    /// all the checks ran, even if some failed
+    fn analysed(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|(_, status)| matches!(status, CheckStatus::Done | CheckStatus::Failed))
+    }
+
+    fn failed(&self) -> bool {
+        self.checks
+            .iter()
+            .any(|(_, status)| *status == CheckStatus::Failed)
+    }
+
+    /// save the scan and its findings in the history
+    fn save_scan(&self, ctx: &Context<Self>) {
+        let Some(snapshot) = &self.snapshot else {
+            return;
+        };
+        let scan = ScanRecord::new(ctx.props().source.info().name, snapshot, self.findings());
+        let link = ctx.link().clone();
+
+        spawn_local(async move {
+            match history::save(&scan).await {
+                Ok(()) => link.send_message(WalletMessage::Saved(Rc::new(scan))),
+                Err(error) => gloo::console::error!(error.to_string()),
+            }
+        });
+    }
+
+    /// share the scan with the other pages: the dashboard of the connected
+    /// wallets and the address inspector
+    fn share_summary(&self, ctx: &Context<Self>, analysed: bool) {
+        let Some(snapshot) = &self.snapshot else {
+            return;
+        };
+        let Some((context, _)) = ctx.link().context::<ContextHandle>(Callback::noop()) else {
+            return;
+        };
+        let info = ctx.props().source.info();
+
+        let findings = analysed.then(|| {
+            let by_address = self.by_address().map(|(utxos, label)| {
+                let count = utxos.iter().flat_map(HashMap::values).map(Vec::len).sum();
+                (label, count)
             });
-            true
-        } else {
-            false
+            let suspicious = self.suspicious_utxos.as_ref().map_or(0, Vec::len);
+            let datums = self
+                .utxos_with_datums_or_scripts
+                .as_ref()
+                .map_or(0, Vec::len);
+            let collateral = self
+                .collateral
+                .as_ref()
+                .map_or(0, |report| report.issues.len());
+            let change_address = self
+                .change_address
+                .as_ref()
+                .map_or(0, |report| report.issues.len());
+            let accounts = self
+                .accounts
+                .as_ref()
+                .filter(|report| report.multiple_accounts())
+                .map_or(0, |report| report.known().count());
+
+            by_address
+                .into_iter()
+                .chain([
+                    ("Suspicious", suspicious),
+                    ("Datum or reference script", datums),
+                    ("Collateral issue", collateral),
+                    ("Accounts", accounts),
+                    ("Change address issue", change_address),
+                ])
+                .collect()
+        });
+
+        context.dispatch(Action::WalletScanned(Rc::new(WalletSummary {
+            name: info.name,
+            icon: info.icon,
+            symbol: self.symbol,
+            balance: snapshot.balance.clone().ok(),
+            utxos: snapshot.utxos.len(),
+            findings,
+            reward_addresses: snapshot.reward_addresses.iter().cloned().collect(),
+            addresses: snapshot.wallet_addresses(),
+        })));
+    }
+
+    /// the findings of the checks, as kept in the history
+    fn findings(&self) -> BTreeSet<String> {
+        let by_utxo = self
+            .utxo_findings()
+            .into_iter()
+            .flat_map(|(utxo, findings)| {
+                findings
+                    .into_iter()
+                    .map(move |finding| format!("{finding}: {utxo}"))
+            });
+        let collateral = self
+            .collateral
+            .iter()
+            .flat_map(|report| &report.issues)
+            .map(|issue| format!("Collateral: {issue}"));
+        let change_address = self
+            .change_address
+            .iter()
+            .flat_map(|report| &report.issues)
+            .map(|issue| format!("Change address: {issue}"));
+        let accounts = self
+            .accounts
+            .iter()
+            .filter(|report| report.multiple_accounts())
+            .flat_map(AccountsReport::known)
+            .filter_map(|account| account.reward_address.as_ref())
+            .map(|address| format!("Account: {}", address.to_bech32().unwrap_or_default()));
+
+        by_utxo
+            .chain(collateral)
+            .chain(change_address)
+            .chain(accounts)
+            .collect()
+    }
+
+    fn set_status(&mut self, check: Check, status: CheckStatus) {
+        for (_, current) in Rc::make_mut(&mut self.checks)
+            .iter_mut()
+            .filter(|(checked, _)| *checked == check)
+        {
+            *current = status;
+        }
+    }
+
+    /// the findings of the checks reporting UTxOs by address, with their label
+    #[allow(clippy::type_complexity)]
+    fn by_address(&self) -> [(&Option<HashMap<Address, Vec<Utxo>>>, &'static str); 6] {
+        [
+            (&self.utxos_with_byron_addresses, "Byron address"),
+            (
+                &self.utxos_with_unknown_reward_addresses,
+                "Unknown staking key",
+            ),
+            (&self.utxos_with_used_addresses, "Used address"),
+            (&self.utxos_with_pointer_addresses, "Pointer address"),
+            (&self.utxos_with_enterprise_addresses, "Enterprise address"),
+            (&self.utxos_with_script_credentials, "Script credential"),
+        ]
+    }
+
+    /// the findings of the checks for each of the UTxOs they mention
+    fn utxo_findings(&self) -> UtxoFindings {
+        let mut findings = UtxoFindings::new();
+        let mut add = |utxo: &Utxo, finding: String| {
+            findings.entry(utxo_id(utxo)).or_default().push(finding);
+        };
+
+        for (utxos, finding) in self.by_address() {
+            for utxo in utxos.iter().flat_map(HashMap::values).flatten() {
+                add(utxo, finding.to_owned());
+            }
         }
+
+        for suspicious in self.suspicious_utxos.iter().flatten() {
+            for reason in &suspicious.reasons {
+                add(&suspicious.utxo, format!("Suspicious: {reason}"));
+            }
+        }
+
+        for (utxo, _) in self.utxos_with_datums_or_scripts.iter().flatten() {
+            add(utxo, "Datum or reference script".to_owned());
+        }
+
+        for report in &self.collateral {
+            for utxo in &report.collateral {
+                add(utxo, "Collateral".to_owned());
+            }
+        }
+
+        findings
     }
 }
@@ -1,86 +1,55 @@
-use cardano_connector::{ConnectedWallet, NetworkId};
-use yew::{platform::spawn_local, prelude::*};
-
-#[derive(Default)]
-pub struct WalletNetworkId {
-    state: NetworkIdState,
+use super::snapshot::WalletSnapshot;
+use serde::{Deserialize, Serialize};
+use std::{fmt, rc::Rc};
+use yew::prelude::*;
+
+/// the network id returned by `getNetworkId`: 1 for mainnet, 0 for the
+/// testnets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NetworkId(pub u8);
+
+impl NetworkId {
+    pub const TESTNET: Self = Self(0);
+    pub const MAINNET: Self = Self(1);
 }
 
-#[derive(Default)]
-enum NetworkIdState {
-    #[default]
-    Collecting,
-    Received(NetworkId),
-    Error(String),
+impl fmt::Display for NetworkId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::MAINNET => f.write_str("Mainnet"),
+            Self::TESTNET => f.write_str("Testnet"),
+            Self(id) => write!(f, "Network {id}"),
+        }
+    }
 }
 
-pub enum Message {
-    CollectNetworkId,
-    NetworkIdReceived(NetworkId),
-    Error(String),
-}
+pub struct WalletNetworkId;
 
 #[derive(Properties, PartialEq)]
 pub struct WalletNetworkIdProperties {
-    pub wallet: ConnectedWallet,
+    /// `None` while the wallet is being scanned
+    pub snapshot: Option<Rc<WalletSnapshot>>,
 }
 
 impl Component for WalletNetworkId {
-    type Message = Message;
+    type Message = ();
     type Properties = WalletNetworkIdProperties;
 
-    fn create(ctx: &Context<Self>) -> Self {
-        ctx.link().send_message(Message::CollectNetworkId);
-
-        Self::default()
-    }
-
-    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
-        if ctx.props().ne(old_props) {
-            ctx.link().send_message(Message::CollectNetworkId);
-            true
-        } else {
-            false
-        }
-    }
-
-    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
-        match msg {
-            Message::CollectNetworkId => {
-                let link = ctx.link().clone();
-                let wallet = ctx.props().wallet.clone();
-
-                spawn_local(async move {
-                    match wallet.network_id().await {
-                        Ok(network_id) => {
-                            link.send_message(Message::NetworkIdReceived(network_id));
-                        }
-                        Err(error) => {
-                            link.send_message(Message::Error(error.to_string()));
-                        }
-                    }
-                });
-
-                let prev = std::mem::replace(&mut self.state, NetworkIdState::Collecting);
-
-                !matches!(prev, NetworkIdState::Collecting)
-            }
-            Message::NetworkIdReceived(network_id) => {
-                self.state = NetworkIdState::Received(network_id);
-                true
-            }
-            Message::Error(error) => {
-                self.state = NetworkIdState::Error(error);
-                true
-            }
-        }
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
     }
 
-    fn view(&self, _ctx: &Context<Self>) -> Html {
+    fn view(&self, ctx: &Context<Self>) -> Html {
         let style;
 
-        let state = match &self.state {
-            NetworkIdState::Collecting => {
+        let state = match ctx
+            .props()
+            .snapshot
+            .as_ref()
+            .map(|snapshot| &snapshot.network_id)
+        {
+            None => {
                 style = "text-bg-secondary";
                 html! {
                     <div class={classes!("spinner-border", "text-primary", "me-2")} role="status">
@@ -88,11 +57,11 @@ impl Component for WalletNetworkId {
                     </div>
                 }
             }
-            NetworkIdState::Received(network_id) => {
+            Some(Ok(network_id)) => {
                 style = "text-bg-primary";
                 html!({ format!("{network_id}") })
             }
-            NetworkIdState::Error(error) => {
+            Some(Err(error)) => {
                 style = "text-bg-warning";
                 gloo::console::error!(format!("{error}"));
 
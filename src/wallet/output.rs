@@ -0,0 +1,386 @@
+//! decode the transaction output of the UTxOs returned by the wallet
+//!
+//! The [`Utxo`] from `cardano_connector` only exposes the address and the
+//! lovelace of the output. We decode the CBOR of the `TransactionUnspentOutput`
+//! ourselves to access the rest of the output (the native assets, the datum,
+//! the reference script and its size).
+
+use cardano_connector::Utxo;
+use pallas_codec::minicbor::{
+    self,
+    data::Type,
+    decode::{Decoder, Error},
+    Encoder,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, convert::Infallible, fmt};
+
+/// hash of the minting policy of a native asset
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct PolicyId(Vec<u8>);
+
+/// name of a native asset within its minting policy, arbitrary bytes
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct AssetName(Vec<u8>);
+
+/// the value held in an output: lovelace and native assets
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Value {
+    pub coin: u64,
+    pub assets: BTreeMap<PolicyId, BTreeMap<AssetName, u64>>,
+}
+
+/// the datum attached to an output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Datum {
+    /// the hash of the datum, the datum itself is provided when spending
+    Hash(Vec<u8>),
+    /// the datum is stored in the output, `size` is in bytes
+    Inline { size: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScriptLanguage {
+    Native,
+    PlutusV1,
+    PlutusV2,
+    PlutusV3,
+    Unknown(u64),
+}
+
+/// a reference script stored in an output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptRef {
+    pub language: ScriptLanguage,
+    /// size of the script, in bytes
+    pub size: usize,
+}
+
+/// the decoded transaction output of a [`Utxo`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Output {
+    pub address: Vec<u8>,
+    pub value: Value,
+    pub datum: Option<Datum>,
+    pub script_ref: Option<ScriptRef>,
+    /// size of the serialized transaction output, in bytes
+    pub size: usize,
+}
+
+impl PolicyId {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AssetName {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// the asset name as a string if it is printable UTF-8
+    pub fn as_utf8(&self) -> Option<&str> {
+        std::str::from_utf8(&self.0)
+            .ok()
+            .filter(|name| !name.is_empty() && !name.chars().any(char::is_control))
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.0)
+    }
+}
+
+impl Value {
+    pub fn has_assets(&self) -> bool {
+        !self.assets.is_empty()
+    }
+
+    /// number of distinct native assets
+    pub fn num_assets(&self) -> usize {
+        self.assets.values().map(BTreeMap::len).sum()
+    }
+
+    pub fn assets(&self) -> impl Iterator<Item = (&PolicyId, &AssetName, u64)> {
+        self.assets.iter().flat_map(|(policy_id, assets)| {
+            assets
+                .iter()
+                .map(move |(name, quantity)| (policy_id, name, *quantity))
+        })
+    }
+
+    /// add the other value to this one, saturating the quantities
+    pub fn add(&mut self, other: &Self) {
+        self.coin = self.coin.saturating_add(other.coin);
+        for (policy_id, name, quantity) in other.assets() {
+            let total = self
+                .assets
+                .entry(policy_id.clone())
+                .or_default()
+                .entry(name.clone())
+                .or_default();
+            *total = total.saturating_add(quantity);
+        }
+    }
+}
+
+/// decode the transaction output of the given UTxO
+pub fn decode_output(utxo: &Utxo) -> Result<Output, Error> {
+    let bytes = minicbor::to_vec(utxo).map_err(|error| Error::message(error.to_string()))?;
+    let mut d = Decoder::new(&bytes);
+
+    // TransactionUnspentOutput = [ TransactionInput, TransactionOutput ]
+    d.array()?;
+    d.skip()?;
+
+    decode_transaction_output(&mut d)
+}
+
+fn decode_transaction_output(d: &mut Decoder<'_>) -> Result<Output, Error> {
+    let start = d.position();
+    let mut address = Vec::new();
+    let mut value = Value::default();
+    let mut datum = None;
+    let mut script_ref = None;
+
+    match d.datatype()? {
+        // legacy output: [ address, value, ? datum_hash ]
+        Type::Array | Type::ArrayIndef => {
+            let len = d.array()?;
+            let mut index = 0;
+            while has_next(d, len, index)? {
+                match index {
+                    0 => address = d.bytes()?.to_vec(),
+                    1 => value = decode_value(d)?,
+                    2 => datum = Some(Datum::Hash(d.bytes()?.to_vec())),
+                    _ => d.skip()?,
+                }
+                index += 1;
+            }
+        }
+        // post alonzo output: { 0: address, 1: value, ? 2: datum_option, ? 3: script_ref }
+        Type::Map | Type::MapIndef => {
+            let len = d.map()?;
+            let mut index = 0;
+            while has_next(d, len, index)? {
+                match d.u64()? {
+                    0 => address = d.bytes()?.to_vec(),
+                    1 => value = decode_value(d)?,
+                    2 => datum = Some(decode_datum_option(d)?),
+                    3 => script_ref = Some(decode_script_ref(d)?),
+                    _ => d.skip()?,
+                }
+                index += 1;
+            }
+        }
+        t => return Err(Error::type_mismatch(t).with_message("expected transaction output")),
+    }
+
+    Ok(Output {
+        address,
+        value,
+        datum,
+        script_ref,
+        size: d.position() - start,
+    })
+}
+
+/// datum_option = [ 0, hash32 // 1, #6.24(bytes .cbor plutus_data) ]
+fn decode_datum_option(d: &mut Decoder<'_>) -> Result<Datum, Error> {
+    d.array()?;
+    match d.u64()? {
+        0 => Ok(Datum::Hash(d.bytes()?.to_vec())),
+        1 => {
+            d.tag()?;
+            Ok(Datum::Inline {
+                size: d.bytes()?.len(),
+            })
+        }
+        t => Err(Error::message(format!("unknown datum option {t}"))),
+    }
+}
+
+/// script_ref = #6.24(bytes .cbor script)
+/// script = [ 0, native_script // 1, plutus_v1 // 2, plutus_v2 // 3, plutus_v3 ]
+fn decode_script_ref(d: &mut Decoder<'_>) -> Result<ScriptRef, Error> {
+    d.tag()?;
+    let bytes = d.bytes()?;
+
+    let mut d = Decoder::new(bytes);
+    d.array()?;
+    let language = match d.u64()? {
+        0 => ScriptLanguage::Native,
+        1 => ScriptLanguage::PlutusV1,
+        2 => ScriptLanguage::PlutusV2,
+        3 => ScriptLanguage::PlutusV3,
+        t => ScriptLanguage::Unknown(t),
+    };
+    let size = match language {
+        ScriptLanguage::PlutusV1 | ScriptLanguage::PlutusV2 | ScriptLanguage::PlutusV3 => {
+            d.bytes()?.len()
+        }
+        ScriptLanguage::Native | ScriptLanguage::Unknown(_) => bytes.len() - d.position(),
+    };
+
+    Ok(ScriptRef { language, size })
+}
+
+/// decode the CBOR of a value, as returned by `getBalance`
+pub fn decode_value_bytes(bytes: &[u8]) -> Result<Value, Error> {
+    decode_value(&mut Decoder::new(bytes))
+}
+
+/// size of a post alonzo transaction output holding the given value
+/// at the given address, without datum nor script
+pub fn output_size(address: &[u8], value: &Value) -> usize {
+    let mut e = Encoder::new(Vec::new());
+    e.map(2)
+        .and_then(|e| e.u8(0)?.bytes(address)?.u8(1))
+        .and_then(|e| encode_value(e, value))
+        .expect("encoding into a Vec is infallible");
+    e.into_writer().len()
+}
+
+/// size of the given value once serialized
+pub fn value_size(value: &Value) -> usize {
+    value_bytes(value).len()
+}
+
+/// the CBOR of the value, as returned by `getBalance`
+pub fn value_bytes(value: &Value) -> Vec<u8> {
+    let mut e = Encoder::new(Vec::new());
+    encode_value(&mut e, value).expect("encoding into a Vec is infallible");
+    e.into_writer()
+}
+
+/// the CBOR of a `TransactionUnspentOutput` with a post alonzo output,
+/// without datum nor script
+pub fn utxo_bytes(transaction_id: &[u8], index: u64, address: &[u8], value: &Value) -> Vec<u8> {
+    let mut e = Encoder::new(Vec::new());
+    e.array(2)
+        .and_then(|e| e.array(2)?.bytes(transaction_id)?.u64(index))
+        .and_then(|e| e.map(2)?.u8(0)?.bytes(address)?.u8(1))
+        .and_then(|e| encode_value(e, value))
+        .expect("encoding into a Vec is infallible");
+    e.into_writer()
+}
+
+fn encode_value<'e>(
+    e: &'e mut Encoder<Vec<u8>>,
+    value: &Value,
+) -> Result<&'e mut Encoder<Vec<u8>>, minicbor::encode::Error<Infallible>> {
+    if !value.has_assets() {
+        return e.u64(value.coin);
+    }
+
+    e.array(2)?
+        .u64(value.coin)?
+        .map(value.assets.len() as u64)?;
+    for (policy_id, assets) in &value.assets {
+        e.bytes(policy_id.as_bytes())?.map(assets.len() as u64)?;
+        for (name, quantity) in assets {
+            e.bytes(name.as_bytes())?.u64(*quantity)?;
+        }
+    }
+
+    Ok(e)
+}
+
+fn decode_value(d: &mut Decoder<'_>) -> Result<Value, Error> {
+    let mut value = Value::default();
+
+    match d.datatype()? {
+        Type::U8 | Type::U16 | Type::U32 | Type::U64 => {
+            value.coin = d.u64()?;
+        }
+        // [ coin, multiasset<uint> ]
+        Type::Array | Type::ArrayIndef => {
+            let len = d.array()?;
+            let mut index = 0;
+            while has_next(d, len, index)? {
+                match index {
+                    0 => value.coin = d.u64()?,
+                    1 => value.assets = decode_multiasset(d)?,
+                    _ => d.skip()?,
+                }
+                index += 1;
+            }
+        }
+        t => return Err(Error::type_mismatch(t).with_message("expected value")),
+    }
+
+    Ok(value)
+}
+
+fn decode_multiasset(
+    d: &mut Decoder<'_>,
+) -> Result<BTreeMap<PolicyId, BTreeMap<AssetName, u64>>, Error> {
+    let mut multiasset = BTreeMap::new();
+
+    let len = d.map()?;
+    let mut index = 0;
+    while has_next(d, len, index)? {
+        let policy_id = PolicyId(d.bytes()?.to_vec());
+        let assets: &mut BTreeMap<_, _> = multiasset.entry(policy_id).or_default();
+
+        let assets_len = d.map()?;
+        let mut asset_index = 0;
+        while has_next(d, assets_len, asset_index)? {
+            let name = AssetName(d.bytes()?.to_vec());
+            let quantity = d.u64()?;
+            assets.insert(name, quantity);
+            asset_index += 1;
+        }
+
+        index += 1;
+    }
+
+    Ok(multiasset)
+}
+
+/// check if there is another element to decode in a definite or indefinite
+/// array or map. The `break` of indefinite length items is consumed.
+pub fn has_next(d: &mut Decoder<'_>, len: Option<u64>, index: u64) -> Result<bool, Error> {
+    match len {
+        Some(len) => Ok(index < len),
+        None if d.datatype()? == Type::Break => {
+            d.set_position(d.position() + 1);
+            Ok(false)
+        }
+        None => Ok(true),
+    }
+}
+
+impl fmt::Display for ScriptLanguage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Native => f.write_str("Native"),
+            Self::PlutusV1 => f.write_str("Plutus V1"),
+            Self::PlutusV2 => f.write_str("Plutus V2"),
+            Self::PlutusV3 => f.write_str("Plutus V3"),
+            Self::Unknown(t) => write!(f, "Unknown ({t})"),
+        }
+    }
+}
+
+impl fmt::Display for PolicyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(&self.0))
+    }
+}
+
+impl fmt::Display for AssetName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.as_utf8() {
+            Some(name) => f.write_str(name),
+            None => f.write_str(&self.to_hex()),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use gloo::storage::{errors::StorageError, LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const STORAGE_KEY: &str = "cardano-web-wallet-analyser.api-policy";
+
+/// how long to wait for the wallet and how often to try again
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiPolicy {
+    /// milliseconds before a call is abandoned, retries included
+    pub timeout: u32,
+    /// the timeout of the calls needing more time, by method
+    pub timeouts: BTreeMap<String, u32>,
+    /// how many times a call failing with an internal error of the wallet
+    /// is made again
+    pub retries: u32,
+    /// milliseconds before the first retry, doubled at each retry
+    pub backoff: u32,
+}
+
+impl Default for ApiPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: 15_000,
+            timeouts: BTreeMap::from([
+                // the user has to approve the connection in the wallet
+                ("enable".to_owned(), 120_000),
+                // large wallets take a while to list their UTxOs
+                ("getUtxos".to_owned(), 60_000),
+            ]),
+            retries: 2,
+            backoff: 1_000,
+        }
+    }
+}
+
+impl ApiPolicy {
+    pub fn load() -> Self {
+        LocalStorage::get(STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), StorageError> {
+        LocalStorage::set(STORAGE_KEY, self)
+    }
+
+    /// the timeout of the given method, in milliseconds
+    pub fn timeout(&self, method: &str) -> u32 {
+        self.timeouts.get(method).copied().unwrap_or(self.timeout)
+    }
+
+    /// the delay before the given retry (starting at 0), in milliseconds
+    pub fn backoff(&self, retry: u32) -> u32 {
+        self.backoff.saturating_mul(2u32.saturating_pow(retry))
+    }
+}
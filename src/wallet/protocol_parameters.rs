@@ -0,0 +1,87 @@
+//! the protocol parameters used to estimate fees and minimum ADA
+//!
+//! The mainnet parameters are bundled with the application
+//! (`assets/protocol-parameters.json`). The user can load the output of
+//! `cardano-cli query protocol-parameters` instead, it is kept in the
+//! browser's local storage.
+
+use gloo::storage::{errors::StorageError, LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+const BUNDLED: &str = include_str!("../../assets/protocol-parameters.json");
+const STORAGE_KEY: &str = "cardano-web-wallet-analyser.protocol-parameters";
+
+/// the constant overhead of an UTxO entry in the ledger, in bytes
+const UTXO_ENTRY_OVERHEAD: u64 = 160;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolParameters {
+    pub collateral_percentage: u64,
+    pub max_collateral_inputs: u64,
+    pub max_tx_size: u64,
+    pub max_value_size: u64,
+    pub tx_fee_fixed: u64,
+    pub tx_fee_per_byte: u64,
+    pub utxo_cost_per_byte: u64,
+    // the sizes below are not protocol parameters but estimates of the
+    // serialized transaction, missing from the output of `cardano-cli`:
+    // they can be tuned in the file loaded by the user
+    /// size of a transaction without inputs, outputs nor witnesses: the
+    /// body and witness set headers, the fee and the time to live
+    #[serde(default = "default_tx_base_size")]
+    pub tx_base_size: u64,
+    /// size of a transaction input: transaction hash and output index
+    #[serde(default = "default_tx_input_size")]
+    pub tx_input_size: u64,
+    /// size of a verification key witness: verification key and signature
+    #[serde(default = "default_vkey_witness_size")]
+    pub vkey_witness_size: u64,
+}
+
+fn default_tx_base_size() -> u64 {
+    100
+}
+
+fn default_tx_input_size() -> u64 {
+    40
+}
+
+fn default_vkey_witness_size() -> u64 {
+    101
+}
+
+impl ProtocolParameters {
+    pub fn bundled() -> Self {
+        serde_json::from_str(BUNDLED).expect("the bundled protocol parameters should be valid")
+    }
+
+    /// the parameters loaded by the user, or the bundled ones
+    pub fn load() -> Self {
+        LocalStorage::get(STORAGE_KEY).unwrap_or_else(|_| Self::bundled())
+    }
+
+    pub fn save(&self) -> Result<(), StorageError> {
+        LocalStorage::set(STORAGE_KEY, self)
+    }
+
+    /// the minimum lovelace an output of the given serialized size must hold
+    pub fn min_ada(&self, output_size: usize) -> u64 {
+        (UTXO_ENTRY_OVERHEAD + output_size as u64).saturating_mul(self.utxo_cost_per_byte)
+    }
+
+    /// the estimated size of a transaction spending `num_inputs` inputs,
+    /// signed by `num_witnesses` keys, with outputs of `outputs_size` bytes
+    pub fn tx_size(&self, num_inputs: u64, num_witnesses: u64, outputs_size: u64) -> u64 {
+        self.tx_base_size
+            .saturating_add(num_inputs.saturating_mul(self.tx_input_size))
+            .saturating_add(num_witnesses.saturating_mul(self.vkey_witness_size))
+            .saturating_add(outputs_size)
+    }
+
+    /// the fee of a transaction of the given serialized size
+    pub fn fee(&self, tx_size: u64) -> u64 {
+        self.tx_fee_fixed
+            .saturating_add(tx_size.saturating_mul(self.tx_fee_per_byte))
+    }
+}
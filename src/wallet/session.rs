@@ -0,0 +1,621 @@
+//! record the responses of the wallet and replay them later
+//!
+//! A session is saved as a JSON fixture: the responses of each CIP-30
+//! method as the wallet returned them, the hex of the CBOR of the UTxOs
+//! and of the balance, the hex of the bytes of the addresses. Replaying a
+//! session goes through [`Api`] like a live wallet, so a bug reported by a
+//! user can be reproduced from the session they saved.
+//!
+//! The session can be redacted before being shared: the credentials of the
+//! addresses and the transaction ids are replaced by pseudonyms. The same
+//! credential always gets the same pseudonym so the relations between the
+//! addresses (same stake key, used address...) are preserved. The root and
+//! the derivation path of the Byron addresses are replaced too, and their
+//! checksum computed again, as are the datum hashes. The inline datums and
+//! the reference scripts cannot be replaced without knowing what they hold:
+//! they are kept as is and the session is not marked redacted.
+//!
+//! [`Api`]: super::api::Api
+
+use super::{
+    api::{ApiError, Call},
+    network_id::NetworkId,
+    output::{decode_output, decode_value_bytes, has_next, Datum, Output},
+};
+use cardano_connector::{Address, Utxo};
+use pallas_codec::minicbor::{self, decode::Decoder};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// the version of the fixture format
+pub const SESSION_VERSION: u32 = 2;
+
+/// the size of the credentials (key and script hashes) in the addresses
+const CREDENTIAL_SIZE: usize = 28;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub version: u32,
+    pub wallet: WalletInfo,
+    pub redacted: bool,
+    /// the last response to each call, see [`key`]
+    pub responses: BTreeMap<String, Response>,
+}
+
+/// the wallet extension the session was recorded with
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WalletInfo {
+    pub name: String,
+    pub icon: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Response {
+    /// hex of the CBOR of each UTxO
+    Utxos(Vec<String>),
+    /// hex of the CBOR of the value
+    Balance(String),
+    NetworkId(NetworkId),
+    /// the CIP numbers of the extensions enabled by the wallet
+    Extensions(Vec<u32>),
+    /// hex of the bytes of the address
+    Address(String),
+    Addresses(Vec<String>),
+    Error(String),
+}
+
+/// the key of the responses: the method, and the parameters if any
+fn key(method: &str, parameters: &str) -> String {
+    if parameters.is_empty() {
+        method.to_owned()
+    } else {
+        format!("{method}({parameters})")
+    }
+}
+
+impl Session {
+    /// the session made of the responses to the given calls
+    ///
+    /// The calls are in the order they were made: the last response to
+    /// a call is the one kept.
+    pub fn from_calls<'a>(wallet: WalletInfo, calls: impl IntoIterator<Item = &'a Call>) -> Self {
+        let responses = calls
+            .into_iter()
+            .map(|call| (key(call.method, &call.parameters), call.response.clone()))
+            .collect();
+
+        Self {
+            version: SESSION_VERSION,
+            wallet,
+            redacted: false,
+            responses,
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let session: Self = serde_json::from_str(json).map_err(|error| error.to_string())?;
+        if session.version != SESSION_VERSION {
+            return Err(format!(
+                "unsupported session version {} (expected {SESSION_VERSION})",
+                session.version
+            ));
+        }
+        Ok(session)
+    }
+
+    /// the recorded response to the call
+    pub fn replay(&self, method: &str, parameters: &str) -> Result<Response, ApiError> {
+        let key = key(method, parameters);
+        match self.responses.get(&key) {
+            None => Err(ApiError::NotRecorded(key)),
+            Some(Response::Error(error)) => Err(ApiError::Recorded(error.clone())),
+            Some(response) => Ok(response.clone()),
+        }
+    }
+
+    /// the same session with the credentials, transaction ids and datum
+    /// hashes replaced, and the number of items kept as is
+    ///
+    /// The session is only marked redacted if nothing was kept.
+    pub fn redact(&self) -> (Self, usize) {
+        let mut redactor = Redactor::default();
+        let responses = self
+            .responses
+            .iter()
+            .map(|(key, response)| (key.clone(), redactor.response(response)))
+            .collect();
+
+        let session = Self {
+            version: self.version,
+            wallet: self.wallet.clone(),
+            redacted: redactor.kept == 0,
+            responses,
+        };
+        (session, redactor.kept)
+    }
+}
+
+/// a replayed session is only equal to itself: comparing the responses
+/// would be costly and the session never changes once loaded
+impl PartialEq for Session {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Response {
+    /// size of the response as the wallet returned it, in bytes: the
+    /// bytes of the hex, or the digits of the network id and of the CIP
+    /// numbers. `None` for errors
+    pub fn size(&self) -> Option<usize> {
+        match self {
+            Self::Utxos(items) | Self::Addresses(items) => {
+                Some(items.iter().map(|item| item.len() / 2).sum())
+            }
+            Self::Balance(value) | Self::Address(value) => Some(value.len() / 2),
+            Self::NetworkId(network_id) => Some(network_id.0.to_string().len()),
+            Self::Extensions(cips) => Some(cips.iter().map(|cip| cip.to_string().len()).sum()),
+            Self::Error(_) => None,
+        }
+    }
+
+    pub fn to_utxos(&self) -> Result<Vec<Utxo>, String> {
+        let Self::Utxos(items) = self else {
+            return Err(self.unexpected("UTxOs"));
+        };
+        items
+            .iter()
+            .map(|item| {
+                let bytes = hex::decode(item).map_err(|error| error.to_string())?;
+                minicbor::decode(&bytes).map_err(|error| error.to_string())
+            })
+            .collect()
+    }
+
+    /// the lovelace of the balance
+    pub fn to_balance(&self) -> Result<u64, String> {
+        let Self::Balance(value) = self else {
+            return Err(self.unexpected("a balance"));
+        };
+        let bytes = hex::decode(value).map_err(|error| error.to_string())?;
+        decode_value_bytes(&bytes)
+            .map(|value| value.coin)
+            .map_err(|error| error.to_string())
+    }
+
+    pub fn to_network_id(&self) -> Result<NetworkId, String> {
+        match self {
+            Self::NetworkId(network_id) => Ok(*network_id),
+            _ => Err(self.unexpected("a network id")),
+        }
+    }
+
+    pub fn to_extensions(&self) -> Result<Vec<u32>, String> {
+        match self {
+            Self::Extensions(cips) => Ok(cips.clone()),
+            _ => Err(self.unexpected("extensions")),
+        }
+    }
+
+    pub fn to_address(&self) -> Result<Address, String> {
+        match self {
+            Self::Address(address) => Address::from_hex(address).map_err(|error| error.to_string()),
+            _ => Err(self.unexpected("an address")),
+        }
+    }
+
+    pub fn to_addresses(&self) -> Result<Vec<Address>, String> {
+        let Self::Addresses(items) = self else {
+            return Err(self.unexpected("addresses"));
+        };
+        items
+            .iter()
+            .map(|address| Address::from_hex(address).map_err(|error| error.to_string()))
+            .collect()
+    }
+
+    /// the hex of the UTxOs or of the addresses
+    pub fn into_items(self) -> Result<Vec<String>, String> {
+        match self {
+            Self::Utxos(items) | Self::Addresses(items) => Ok(items),
+            _ => Err(self.unexpected("UTxOs or addresses")),
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> String {
+        let found = match self {
+            Self::Utxos(_) => "UTxOs",
+            Self::Balance(_) => "a balance",
+            Self::NetworkId(_) => "a network id",
+            Self::Extensions(_) => "extensions",
+            Self::Address(_) => "an address",
+            Self::Addresses(_) => "addresses",
+            Self::Error(_) => "an error",
+        };
+        format!("expected {expected}, found {found}")
+    }
+}
+
+/// replace the hashes with pseudonyms, consistently across the session
+#[derive(Default)]
+struct Redactor {
+    pseudonyms: HashMap<Vec<u8>, Vec<u8>>,
+    /// the inline datums, reference scripts and addresses kept as is
+    kept: usize,
+}
+
+impl Redactor {
+    fn response(&mut self, response: &Response) -> Response {
+        match response {
+            Response::Utxos(items) => {
+                Response::Utxos(items.iter().map(|item| self.utxo(item)).collect())
+            }
+            Response::Address(address) => Response::Address(self.address_hex(address)),
+            Response::Addresses(items) => {
+                Response::Addresses(items.iter().map(|item| self.address_hex(item)).collect())
+            }
+            other => other.clone(),
+        }
+    }
+
+    /// the pseudonym of the hash, of the same length
+    ///
+    /// The pseudonyms are numbered so they are easy to recognize.
+    fn pseudonym(&mut self, hash: &[u8]) -> Vec<u8> {
+        let next = self.pseudonyms.len() as u64 + 1;
+        self.pseudonyms
+            .entry(hash.to_vec())
+            .or_insert_with(|| {
+                let mut pseudonym = vec![0; hash.len()];
+                let counter = next.to_be_bytes();
+                let len = counter.len().min(hash.len());
+                pseudonym[hash.len() - len..].copy_from_slice(&counter[counter.len() - len..]);
+                pseudonym
+            })
+            .clone()
+    }
+
+    fn address_hex(&mut self, address: &str) -> String {
+        match hex::decode(address) {
+            Ok(bytes) => hex::encode(self.address(&bytes)),
+            Err(_) => address.to_owned(),
+        }
+    }
+
+    /// replace the credentials of an address, keeping its header
+    fn address(&mut self, bytes: &[u8]) -> Vec<u8> {
+        let Some(header) = bytes.first() else {
+            return Vec::new();
+        };
+        let num_credentials = match header >> 4 {
+            // base addresses: payment and stake credentials
+            0..=3 => 2,
+            // pointer, enterprise and reward addresses
+            4..=7 | 14 | 15 => 1,
+            8 => {
+                return self.byron_address(bytes).unwrap_or_else(|| {
+                    self.kept += 1;
+                    bytes.to_vec()
+                })
+            }
+            _ => 0,
+        };
+
+        let mut redacted = bytes.to_vec();
+        for index in 0..num_credentials {
+            let start = 1 + index * CREDENTIAL_SIZE;
+            let Some(credential) = bytes.get(start..start + CREDENTIAL_SIZE) else {
+                break;
+            };
+            let pseudonym = self.pseudonym(credential);
+            redacted[start..start + CREDENTIAL_SIZE].copy_from_slice(&pseudonym);
+        }
+        redacted
+    }
+
+    /// replace the root and the derivation path of a Byron address and
+    /// compute its checksum again, `None` if the address cannot be decoded
+    ///
+    /// ```text
+    /// address    = [ #6.24(bytes .cbor [ root, attributes, type ]), crc32 ]
+    /// attributes = { ? 1 : bytes .cbor bytes, ? 2 : bytes .cbor uint }
+    /// ```
+    ///
+    /// The attribute 1 is the encrypted derivation path of the Daedalus
+    /// wallets, the attribute 2 the protocol magic of the testnets.
+    fn byron_address(&mut self, bytes: &[u8]) -> Option<Vec<u8>> {
+        let mut d = Decoder::new(bytes);
+        d.array().ok()?;
+        d.tag().ok()?;
+        let payload = d.bytes().ok()?;
+        let payload_end = d.position();
+        let payload_start = payload_end - payload.len();
+        let crc_start = d.position();
+        d.u32().ok()?;
+        let crc_end = d.position();
+
+        let mut redacted = bytes.to_vec();
+        let mut p = Decoder::new(payload);
+        p.array().ok()?;
+        let root = p.bytes().ok()?;
+        let root_start = payload_start + p.position() - root.len();
+        let pseudonym = self.pseudonym(root);
+        redacted[root_start..root_start + root.len()].copy_from_slice(&pseudonym);
+
+        let len = p.map().ok()?;
+        let mut index = 0;
+        while has_next(&mut p, len, index).ok()? {
+            let key = p.u64().ok()?;
+            let value = p.bytes().ok()?;
+            if key == 1 {
+                let value_start = payload_start + p.position() - value.len();
+                let mut v = Decoder::new(value);
+                let path = v.bytes().ok()?;
+                let path_start = value_start + v.position() - path.len();
+                let pseudonym = self.pseudonym(path);
+                redacted[path_start..path_start + path.len()].copy_from_slice(&pseudonym);
+            }
+            index += 1;
+        }
+
+        // the checksum keeps the width it was encoded with so the address
+        // keeps its length, and can be substituted in the UTxOs
+        let crc = crc32(&redacted[payload_start..payload_end]);
+        let encoded = &mut redacted[crc_start + 1..crc_end];
+        match encoded.len() {
+            4 => encoded.copy_from_slice(&crc.to_be_bytes()),
+            2 => encoded.copy_from_slice(&u16::try_from(crc).ok()?.to_be_bytes()),
+            1 => encoded[0] = u8::try_from(crc).ok()?,
+            _ => return None,
+        }
+        Some(redacted)
+    }
+
+    /// replace the transaction id, the address and the datum hash of the
+    /// UTxO
+    ///
+    /// The pseudonyms have the length of the original bytes so they can be
+    /// substituted in the CBOR without encoding it again.
+    fn utxo(&mut self, item: &str) -> String {
+        let Ok(mut bytes) = hex::decode(item) else {
+            self.kept += 1;
+            return item.to_owned();
+        };
+        let Ok((transaction_id, output)) = utxo_parts(&bytes) else {
+            self.kept += 1;
+            return item.to_owned();
+        };
+
+        let pseudonym = self.pseudonym(&transaction_id);
+        replace(&mut bytes, &transaction_id, &pseudonym);
+        let redacted = self.address(&output.address);
+        replace(&mut bytes, &output.address, &redacted);
+        match &output.datum {
+            Some(Datum::Hash(hash)) => {
+                let pseudonym = self.pseudonym(hash);
+                replace(&mut bytes, hash, &pseudonym);
+            }
+            Some(Datum::Inline { .. }) => self.kept += 1,
+            None => {}
+        }
+        if output.script_ref.is_some() {
+            self.kept += 1;
+        }
+
+        hex::encode(bytes)
+    }
+}
+
+/// the transaction id and the output of a `TransactionUnspentOutput`
+fn utxo_parts(bytes: &[u8]) -> Result<(Vec<u8>, Output), minicbor::decode::Error> {
+    let utxo: Utxo = minicbor::decode(bytes)?;
+    let output = decode_output(&utxo)?;
+
+    // TransactionUnspentOutput = [ [ transaction_id, index ], TransactionOutput ]
+    let mut d = Decoder::new(bytes);
+    d.array()?;
+    d.array()?;
+    let transaction_id = d.bytes()?.to_vec();
+
+    Ok((transaction_id, output))
+}
+
+/// the CRC-32 (IEEE) checksum of the Byron addresses
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// replace every occurrence of `from` with `to`, of the same length
+fn replace(bytes: &mut [u8], from: &[u8], to: &[u8]) {
+    if from.is_empty() || from.len() != to.len() {
+        return;
+    }
+    let mut start = 0;
+    while start + from.len() <= bytes.len() {
+        if &bytes[start..start + from.len()] == from {
+            bytes[start..start + from.len()].copy_from_slice(to);
+            start += from.len();
+        } else {
+            start += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::output::{utxo_bytes, Value};
+    use pallas_codec::minicbor::Encoder;
+
+    fn base(payment: u8, stake: u8) -> Vec<u8> {
+        [&[0x01][..], &[payment; 28][..], &[stake; 28][..]].concat()
+    }
+
+    fn ada(coin: u64) -> Value {
+        Value {
+            coin,
+            ..Value::default()
+        }
+    }
+
+    /// a Daedalus address: its attributes hold the encrypted derivation path
+    fn byron(root: u8, path: u8) -> Vec<u8> {
+        let mut path_cbor = Encoder::new(Vec::new());
+        path_cbor.bytes(&[path; 28]).unwrap();
+        let mut payload = Encoder::new(Vec::new());
+        payload
+            .array(3)
+            .and_then(|e| e.bytes(&[root; 28]))
+            .and_then(|e| e.map(1)?.u8(1)?.bytes(path_cbor.writer()))
+            .and_then(|e| e.u8(0))
+            .unwrap();
+        let payload = payload.into_writer();
+
+        let mut address = Encoder::new(Vec::new());
+        address
+            .array(2)
+            .and_then(|e| e.tag(minicbor::data::Tag::new(24))?.bytes(&payload))
+            .and_then(|e| e.u32(crc32(&payload)))
+            .unwrap();
+        address.into_writer()
+    }
+
+    /// the payload of the Byron address, checked against its checksum
+    fn byron_payload(address: &[u8]) -> Vec<u8> {
+        let mut d = Decoder::new(address);
+        d.array().unwrap();
+        d.tag().unwrap();
+        let payload = d.bytes().unwrap().to_vec();
+        assert_eq!(d.u32().unwrap(), crc32(&payload));
+        payload
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn consistent_pseudonyms() {
+        let mut redactor = Redactor::default();
+        let first = redactor.address(&base(0x11, 0x51));
+        let second = redactor.address(&base(0x12, 0x51));
+
+        assert_eq!(first.len(), 57);
+        assert_eq!(first[0], 0x01);
+        assert_ne!(first[1..29], [0x11; 28]);
+        assert_ne!(first[1..29], second[1..29]);
+        // the same stake key gets the same pseudonym
+        assert_eq!(first[29..], second[29..]);
+        assert_eq!(redactor.address(&base(0x11, 0x51)), first);
+        assert_eq!(redactor.kept, 0);
+    }
+
+    #[test]
+    fn redacted_utxo_decodes() {
+        let address = base(0x11, 0x51);
+        let item = hex::encode(utxo_bytes(&[0x01; 32], 3, &address, &ada(5_000_000)));
+
+        let mut redactor = Redactor::default();
+        let redacted = hex::decode(redactor.utxo(&item)).unwrap();
+        assert_eq!(redactor.kept, 0);
+
+        let utxo: Utxo = minicbor::decode(&redacted).unwrap();
+        let output = decode_output(&utxo).unwrap();
+        assert_eq!(output.address, redactor.address(&address));
+        assert_eq!(output.value.coin, 5_000_000);
+        let (transaction_id, _) = utxo_parts(&redacted).unwrap();
+        assert_eq!(transaction_id, redactor.pseudonym(&[0x01; 32]));
+    }
+
+    #[test]
+    fn redacted_byron_address() {
+        let address = byron(0x21, 0x22);
+        let mut redactor = Redactor::default();
+        let redacted = redactor.address(&address);
+
+        assert_eq!(redacted.len(), address.len());
+        assert_eq!(redactor.kept, 0);
+        let payload = byron_payload(&redacted);
+        let contains = |bytes: &[u8]| payload.windows(28).any(|window| window == bytes);
+        assert!(!contains(&[0x21; 28]));
+        assert!(!contains(&[0x22; 28]));
+        assert!(contains(&redactor.pseudonym(&[0x21; 28])));
+        assert!(contains(&redactor.pseudonym(&[0x22; 28])));
+        assert!(Address::from_hex(&hex::encode(&redacted)).is_ok());
+
+        // in the UTxOs too
+        let item = hex::encode(utxo_bytes(&[0x01; 32], 0, &address, &ada(1_000_000)));
+        let utxo: Utxo = minicbor::decode(&hex::decode(redactor.utxo(&item)).unwrap()).unwrap();
+        assert_eq!(decode_output(&utxo).unwrap().address, redacted);
+    }
+
+    /// a UTxO with a datum option: `[0, hash]` or `[1, #6.24(bytes)]`
+    fn utxo_with_datum(datum: impl FnOnce(&mut Encoder<Vec<u8>>)) -> String {
+        let mut e = Encoder::new(Vec::new());
+        e.array(2)
+            .and_then(|e| e.array(2)?.bytes(&[0x01; 32])?.u8(0))
+            .and_then(|e| {
+                e.map(3)?
+                    .u8(0)?
+                    .bytes(&base(0x11, 0x51))?
+                    .u8(1)?
+                    .u64(2_000_000)
+            })
+            .and_then(|e| e.u8(2))
+            .unwrap();
+        datum(&mut e);
+        hex::encode(e.into_writer())
+    }
+
+    #[test]
+    fn redacted_datum_hash() {
+        let item = utxo_with_datum(|e| {
+            e.array(2)
+                .and_then(|e| e.u8(0)?.bytes(&[0x31; 32]))
+                .unwrap();
+        });
+        let mut redactor = Redactor::default();
+        let redacted = hex::decode(redactor.utxo(&item)).unwrap();
+        assert_eq!(redactor.kept, 0);
+
+        let utxo: Utxo = minicbor::decode(&redacted).unwrap();
+        let datum = decode_output(&utxo).unwrap().datum;
+        assert_eq!(datum, Some(Datum::Hash(redactor.pseudonym(&[0x31; 32]))));
+    }
+
+    #[test]
+    fn kept_items_leave_the_session_unmarked() {
+        let inline_datum = utxo_with_datum(|e| {
+            e.array(2)
+                .and_then(|e| e.u8(1)?.tag(minicbor::data::Tag::new(24))?.bytes(&[0x00]))
+                .unwrap();
+        });
+        let mut session = Session::from_calls(WalletInfo::default(), std::iter::empty());
+        session
+            .responses
+            .insert("getUtxos".to_owned(), Response::Utxos(vec![inline_datum]));
+        // a Byron header on bytes that are not a Byron address
+        session.responses.insert(
+            "getUsedAddresses".to_owned(),
+            Response::Addresses(vec!["82ff".to_owned()]),
+        );
+
+        let (redacted, kept) = session.redact();
+        assert_eq!(kept, 2);
+        assert!(!redacted.redacted);
+    }
+}
@@ -0,0 +1,181 @@
+use super::{
+    coin_selection::{select, Algorithm, Selection, SelectionError},
+    lovelace::{Lovelace, Symbol},
+    output::Output,
+    protocol_parameters::ProtocolParameters,
+};
+use cardano_connector::Utxo;
+use std::rc::Rc;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// run the coin selection algorithms side by side over the wallet's UTxOs
+#[derive(Default)]
+pub struct CoinSelectionSimulator {
+    amount: String,
+    results: Option<Vec<(Algorithm, Result<Selection, SelectionError>)>>,
+    error: Option<String>,
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    pub utxos: Rc<Vec<(Utxo, Option<Output>)>>,
+    pub parameters: ProtocolParameters,
+    #[prop_or_default]
+    pub symbol: Symbol,
+}
+
+pub enum Message {
+    AmountChanged(String),
+    Simulate,
+}
+
+impl Component for CoinSelectionSimulator {
+    type Message = Message;
+    type Properties = Properties;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self::default()
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        if ctx.props() != old_props {
+            self.results = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::AmountChanged(amount) => {
+                self.amount = amount;
+                false
+            }
+            Message::Simulate => {
+                let Ok(target) = self.amount.parse::<Lovelace>() else {
+                    self.error = Some(format!("\"{}\" is not a valid ADA amount", self.amount));
+                    self.results = None;
+                    return true;
+                };
+
+                let Properties {
+                    utxos, parameters, ..
+                } = ctx.props();
+                self.error = None;
+                self.results = Some(
+                    [
+                        Algorithm::LargestFirst,
+                        Algorithm::RandomImprove,
+                        Algorithm::Optimal,
+                    ]
+                    .into_iter()
+                    .map(|algorithm| {
+                        (
+                            algorithm,
+                            select(algorithm, parameters, utxos, target.as_u64()),
+                        )
+                    })
+                    .collect(),
+                );
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let oninput = ctx.link().callback(|event: InputEvent| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            Message::AmountChanged(input.value())
+        });
+        let onsubmit = ctx.link().callback(|event: SubmitEvent| {
+            event.prevent_default();
+            Message::Simulate
+        });
+
+        let utxos = &ctx.props().utxos;
+        let symbol = ctx.props().symbol;
+
+        html! {
+            <div class="card mb-3">
+                <div class="card-body">
+                    <h5 class="card-title">{"Coin Selection Simulator"}</h5>
+                    <p class="card-text text-muted">
+                        {"See which of your UTxOs a wallet would spend to pay the given amount, depending on its coin selection algorithm."}
+                    </p>
+                    <form class="d-flex mb-3" onsubmit={onsubmit}>
+                        <div class="input-group me-2">
+                            <input type="text" class="form-control" placeholder="Amount to pay" value={self.amount.clone()} oninput={oninput} />
+                            <span class="input-group-text">{symbol.to_string()}</span>
+                        </div>
+                        <button type="submit" class="btn btn-primary">{"Simulate"}</button>
+                    </form>
+                    if let Some(error) = &self.error {
+                        <div class="text-danger small">{error.clone()}</div>
+                    }
+                    if let Some(results) = &self.results {
+                        <div class="row">
+                            { for results.iter().map(|(algorithm, result)| view_result(*algorithm, result, utxos, symbol)) }
+                        </div>
+                    }
+                </div>
+            </div>
+        }
+    }
+}
+
+fn view_result(
+    algorithm: Algorithm,
+    result: &Result<Selection, SelectionError>,
+    utxos: &[(Utxo, Option<Output>)],
+    symbol: Symbol,
+) -> Html {
+    let body = match result {
+        Err(error) => html! { <p class="text-danger">{error.to_string()}</p> },
+        Ok(selection) => {
+            let fee = Lovelace::new(selection.fee).display(symbol);
+            let change = Lovelace::new(selection.change.coin).display(symbol);
+            let privacy = if selection.linked_addresses > 1 {
+                format!(
+                    "Spending these inputs together publicly links {} of your addresses.",
+                    selection.linked_addresses
+                )
+            } else {
+                "No addresses are linked together by this transaction.".to_owned()
+            };
+
+            html! {
+                <>
+                    <ul class="list-unstyled small">
+                        <li>{format!("{} inputs, about {} bytes", selection.inputs.len(), selection.tx_size)}</li>
+                        <li>{format!("Fee: {fee}")}</li>
+                        <li>{format!("Change: {change}")}</li>
+                        if selection.change.has_assets() {
+                            <li>{format!("Change carries {} native assets", selection.change.num_assets())}</li>
+                        }
+                        <li>{privacy}</li>
+                    </ul>
+                    <ul class="small">
+                        { for selection.inputs.iter().map(|index| {
+                            let utxo = &utxos[*index].0;
+                            let amount = Lovelace::new(utxo.amount()).display(symbol);
+                            html! {
+                                <li class="text-truncate">
+                                    {format!("{amount} - {}#{}", utxo.transaction_id(), utxo.index())}
+                                </li>
+                            }
+                        }) }
+                    </ul>
+                </>
+            }
+        }
+    };
+
+    html! {
+        <div class="col-md-4">
+            <h6>{algorithm.to_string()}</h6>
+            {body}
+        </div>
+    }
+}
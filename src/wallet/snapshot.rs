@@ -0,0 +1,292 @@
+//! everything the wallet reported during a scan
+//!
+//! The wallet is queried once per scan and every view and check works on
+//! the same [`WalletSnapshot`], so they all agree on the state of the
+//! wallet.
+
+use super::{
+    api::{Api, ApiError},
+    cbor::RawResponse,
+    lovelace::Lovelace,
+    network_id::NetworkId,
+    session::Response,
+    tasks::collateral::COLLATERAL_AMOUNT,
+};
+use cardano_connector::{Address, Utxo};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[cfg(test)]
+use super::session::Session;
+
+#[derive(Clone)]
+pub struct WalletSnapshot {
+    pub utxos: Vec<Utxo>,
+    /// not all the wallets implement `getCollateral`, a failure is
+    /// reported by the collateral check instead of failing the scan
+    pub collateral: Result<Vec<Utxo>, String>,
+    pub balance: Result<Lovelace, String>,
+    pub network_id: Result<NetworkId, String>,
+    /// the CIP numbers of the extensions enabled by the wallet
+    pub extensions: Result<Vec<u32>, String>,
+    pub change_address: Address,
+    pub used_addresses: Vec<Address>,
+    pub unused_addresses: Vec<Address>,
+    pub reward_addresses: Vec<Address>,
+    /// the responses the snapshot was decoded from
+    record: SnapshotRecord,
+}
+
+/// the responses of the wallet as it returned them, like in a [`Session`]:
+/// kept in the history and sent to the analysis worker
+///
+/// [`Session`]: super::session::Session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotRecord {
+    utxos: Response,
+    collateral: Response,
+    balance: Response,
+    network_id: Response,
+    /// missing from the scans kept before it was recorded
+    #[serde(default = "not_recorded")]
+    extensions: Response,
+    change_address: Response,
+    used_addresses: Response,
+    unused_addresses: Response,
+    reward_addresses: Response,
+}
+
+impl WalletSnapshot {
+    /// query the wallet, fails if one of the calls the checks need fails
+    pub async fn fetch(wallet: &Api) -> Result<Self, ApiError> {
+        // not all the wallets implement `getCollateral`, and the checks
+        // don't need the balance, the network id nor the extensions: their
+        // errors are kept
+        let or_error = |result: Result<Response, ApiError>| {
+            result.unwrap_or_else(|error| Response::Error(error.to_string()))
+        };
+
+        let record = SnapshotRecord {
+            utxos: wallet.all_utxos().await?,
+            collateral: or_error(wallet.collateral(COLLATERAL_AMOUNT).await),
+            balance: or_error(wallet.balance().await),
+            network_id: or_error(wallet.network_id().await),
+            extensions: or_error(wallet.extensions().await),
+            change_address: wallet.change_address().await?,
+            used_addresses: wallet.used_addresses().await?,
+            unused_addresses: wallet.unused_addresses().await?,
+            reward_addresses: wallet.reward_addresses().await?,
+        };
+        Self::from_record(record).map_err(ApiError::Invalid)
+    }
+
+    /// the addresses reported by the wallet: used, unused and change
+    pub fn wallet_addresses(&self) -> HashSet<Address> {
+        self.used_addresses
+            .iter()
+            .chain(&self.unused_addresses)
+            .chain(std::iter::once(&self.change_address))
+            .cloned()
+            .collect()
+    }
+
+    pub fn record(&self) -> SnapshotRecord {
+        self.record.clone()
+    }
+
+    pub fn from_record(record: SnapshotRecord) -> Result<Self, String> {
+        // the calls allowed to fail keep their error
+        let or_error = |response: &Response| match response {
+            Response::Error(error) => Err(error.clone()),
+            _ => Ok(()),
+        };
+
+        Ok(Self {
+            utxos: record.utxos.to_utxos()?,
+            collateral: or_error(&record.collateral).and_then(|()| record.collateral.to_utxos()),
+            balance: or_error(&record.balance)
+                .and_then(|()| record.balance.to_balance())
+                .map(Lovelace::new),
+            network_id: or_error(&record.network_id)
+                .and_then(|()| record.network_id.to_network_id()),
+            extensions: or_error(&record.extensions)
+                .and_then(|()| record.extensions.to_extensions()),
+            change_address: record.change_address.to_address()?,
+            used_addresses: record.used_addresses.to_addresses()?,
+            unused_addresses: record.unused_addresses.to_addresses()?,
+            reward_addresses: record.reward_addresses.to_addresses()?,
+            record,
+        })
+    }
+
+    /// the responses of the wallet, for the debug panel
+    pub fn raw_responses(&self) -> Vec<RawResponse> {
+        let record = &self.record;
+        [
+            ("getUtxos", &record.utxos),
+            ("getCollateral", &record.collateral),
+            ("getBalance", &record.balance),
+            ("getNetworkId", &record.network_id),
+            ("getExtensions", &record.extensions),
+            ("getChangeAddress", &record.change_address),
+            ("getUsedAddresses", &record.used_addresses),
+            ("getUnusedAddresses", &record.unused_addresses),
+            ("getRewardAddresses", &record.reward_addresses),
+        ]
+        .into_iter()
+        .map(|(method, response)| RawResponse::new(method, response))
+        .collect()
+    }
+}
+
+fn not_recorded() -> Response {
+    Response::Error("not recorded".to_owned())
+}
+
+/// a snapshot is only equal to itself: each scan makes a new one
+impl PartialEq for WalletSnapshot {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+/// the `byron` demo wallet, saved redacted from the API activity panel
+#[cfg(test)]
+pub(crate) fn byron_leftovers() -> Session {
+    Session::from_json(include_str!(
+        "../../tests/fixtures/byron-leftovers.session.json"
+    ))
+    .expect("the fixture should load")
+}
+
+#[cfg(test)]
+impl WalletSnapshot {
+    /// the snapshot a scan takes of the recorded wallet
+    pub(crate) fn replay(session: &Session) -> Self {
+        let response = |method: &str, parameters: &str| {
+            session
+                .replay(method, parameters)
+                .unwrap_or_else(|error| Response::Error(error.to_string()))
+        };
+        let record = SnapshotRecord {
+            utxos: response("getUtxos", ""),
+            collateral: response("getCollateral", &format!("amount: {COLLATERAL_AMOUNT}")),
+            balance: response("getBalance", ""),
+            network_id: response("getNetworkId", ""),
+            extensions: response("getExtensions", ""),
+            change_address: response("getChangeAddress", ""),
+            used_addresses: response("getUsedAddresses", ""),
+            unused_addresses: response("getUnusedAddresses", ""),
+            reward_addresses: response("getRewardAddresses", ""),
+        };
+        Self::from_record(record).expect("the recorded responses should decode")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::{
+        protocol_parameters::ProtocolParameters,
+        tasks::{AlertMessage, Check},
+        token_lists::TokenLists,
+    };
+
+    fn run(check: Check, snapshot: &WalletSnapshot) -> AlertMessage {
+        check
+            .run(
+                snapshot,
+                &ProtocolParameters::bundled(),
+                &TokenLists::bundled(),
+            )
+            .expect("the check should complete")
+    }
+
+    #[test]
+    fn replayed_responses() {
+        let snapshot = WalletSnapshot::replay(&byron_leftovers());
+
+        assert_eq!(snapshot.utxos.len(), 4);
+        assert_eq!(snapshot.collateral.as_ref().map(Vec::len), Ok(1));
+        assert_eq!(snapshot.balance, Ok(Lovelace::new(1_333_400_000)));
+        assert_eq!(snapshot.network_id, Ok(NetworkId::MAINNET));
+        assert_eq!(snapshot.used_addresses.len(), 3);
+        assert_eq!(
+            snapshot.unused_addresses,
+            vec![snapshot.change_address.clone()]
+        );
+        assert_eq!(snapshot.reward_addresses.len(), 1);
+    }
+
+    #[test]
+    fn byron_leftovers_findings() {
+        let snapshot = WalletSnapshot::replay(&byron_leftovers());
+
+        let AlertMessage::UTxOsWithByronAddress { utxos } = run(Check::ByronAddresses, &snapshot)
+        else {
+            panic!("expected the Byron addresses");
+        };
+        assert_eq!(utxos.len(), 1);
+        assert_eq!(utxos.values().map(Vec::len).sum::<usize>(), 2);
+
+        let AlertMessage::UTxOsWithEnterpriseAddresses { utxos } =
+            run(Check::EnterpriseAddresses, &snapshot)
+        else {
+            panic!("expected the enterprise addresses");
+        };
+        assert!(utxos.is_empty());
+
+        let AlertMessage::UTxOsWithUnknownRewardAddresses { utxos } =
+            run(Check::RewardKeys, &snapshot)
+        else {
+            panic!("expected the unknown reward addresses");
+        };
+        assert!(utxos.is_empty());
+
+        let AlertMessage::Collateral { report } = run(Check::Collateral, &snapshot) else {
+            panic!("expected the collateral report");
+        };
+        assert_eq!(report.collateral.len(), 1);
+        assert!(report.issues.is_empty());
+
+        let AlertMessage::Accounts { report } = run(Check::Accounts, &snapshot) else {
+            panic!("expected the accounts");
+        };
+        assert!(!report.multiple_accounts());
+        let without_stake_key = report
+            .accounts
+            .iter()
+            .find(|account| account.reward_address.is_none())
+            .expect("the Byron UTxOs have no stake key");
+        assert_eq!(without_stake_key.utxos.len(), 2);
+
+        let AlertMessage::ChangeAddress { report } = run(Check::ChangeAddress, &snapshot) else {
+            panic!("expected the change address report");
+        };
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn redacted_again() {
+        let (session, kept) = byron_leftovers().redact();
+        assert_eq!(kept, 0);
+        assert!(session.redacted);
+
+        // the pseudonyms keep the relations between the addresses
+        let snapshot = WalletSnapshot::replay(&session);
+        let AlertMessage::UTxOsWithByronAddress { utxos } = run(Check::ByronAddresses, &snapshot)
+        else {
+            panic!("expected the Byron addresses");
+        };
+        assert_eq!(utxos.values().map(Vec::len).sum::<usize>(), 2);
+
+        let AlertMessage::ChangeAddress { report } = run(Check::ChangeAddress, &snapshot) else {
+            panic!("expected the change address report");
+        };
+        assert!(report.issues.is_empty());
+        let AlertMessage::Collateral { report } = run(Check::Collateral, &snapshot) else {
+            panic!("expected the collateral report");
+        };
+        assert!(report.issues.is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+use crate::wallet::snapshot::WalletSnapshot;
+use cardano_connector::{Address, Utxo};
+use pallas_addresses::StakeAddress;
+use std::collections::HashMap;
+
+/// the addresses and UTxOs of the wallet sharing a stake key
+#[derive(Clone, PartialEq)]
+pub struct Account {
+    /// `None` for the addresses without a stake key: Byron, enterprise
+    /// and pointer addresses, and the addresses that cannot be decoded
+    pub reward_address: Option<Address>,
+    /// returned by `getRewardAddresses`
+    pub known: bool,
+    /// the used, unused and change addresses of the account
+    pub addresses: Vec<Address>,
+    pub utxos: Vec<Utxo>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct AccountsReport {
+    /// the accounts returned by the wallet first, in its order
+    pub accounts: Vec<Account>,
+}
+
+impl AccountsReport {
+    /// the reward addresses returned by the wallet
+    pub fn known(&self) -> impl Iterator<Item = &Account> {
+        self.accounts.iter().filter(|account| account.known)
+    }
+
+    /// CIP-30 wallets are connected to a single account, they are expected
+    /// to return one reward address
+    pub fn multiple_accounts(&self) -> bool {
+        self.known().count() > 1
+    }
+}
+
+/// group the addresses and UTxOs of the wallet by stake key
+pub fn accounts(snapshot: &WalletSnapshot) -> Result<AccountsReport, ()> {
+    let mut accounts: Vec<Account> = snapshot
+        .reward_addresses
+        .iter()
+        .map(|reward_address| Account {
+            reward_address: Some(reward_address.clone()),
+            known: true,
+            addresses: Vec::new(),
+            utxos: Vec::new(),
+        })
+        .collect();
+    let mut positions: HashMap<Option<Address>, usize> = accounts
+        .iter()
+        .enumerate()
+        .map(|(position, account)| (account.reward_address.clone(), position))
+        .collect();
+
+    // the position of the account of the stake key, added if unknown
+    let mut account = |reward_address: Option<Address>| {
+        *positions.entry(reward_address.clone()).or_insert_with(|| {
+            accounts.push(Account {
+                reward_address,
+                known: false,
+                addresses: Vec::new(),
+                utxos: Vec::new(),
+            });
+            accounts.len() - 1
+        })
+    };
+
+    let mut addresses: Vec<_> = snapshot.wallet_addresses().into_iter().collect();
+    addresses.sort_by_key(Address::to_hex);
+    let addresses: Vec<_> = addresses
+        .into_iter()
+        .map(|address| (account(stake_address(&address)), address))
+        .collect();
+
+    let mut utxos = Vec::with_capacity(snapshot.utxos.len());
+    for utxo in &snapshot.utxos {
+        // kept without stake key so the accounts add up to the balance
+        let reward_address = match utxo.address() {
+            Ok(address) => stake_address(&address),
+            Err(error) => {
+                gloo::console::error!(error.to_string());
+                None
+            }
+        };
+        utxos.push((account(reward_address), utxo.clone()));
+    }
+
+    for (position, address) in addresses {
+        accounts[position].addresses.push(address);
+    }
+    for (position, utxo) in utxos {
+        accounts[position].utxos.push(utxo);
+    }
+
+    Ok(AccountsReport { accounts })
+}
+
+fn stake_address(address: &Address) -> Option<Address> {
+    match address {
+        Address::Shelley(shelley) => StakeAddress::try_from(shelley.clone())
+            .ok()
+            .map(Address::Stake),
+        Address::Stake(_) => Some(address.clone()),
+        Address::Byron(_) => None,
+    }
+}
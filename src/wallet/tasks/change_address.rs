@@ -0,0 +1,279 @@
+use crate::wallet::{
+    lovelace::{Lovelace, Symbol},
+    snapshot::WalletSnapshot,
+};
+use cardano_connector::Address;
+use pallas_addresses::{Network, ShelleyDelegationPart, StakeAddress};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Clone, PartialEq)]
+pub struct ChangeAddressReport {
+    pub address: Address,
+    pub issues: Vec<ChangeAddressIssue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeAddressIssue {
+    /// the change goes to a Byron address
+    Byron,
+    /// the change goes to an address without a stake part
+    Enterprise,
+    /// the change address is a reward address, which cannot receive
+    /// transaction outputs
+    StakeAddress,
+    /// the change delegates to a stake key the wallet did not return
+    ForeignStakeKey { stake_address: String },
+    /// the network tag of the change address is not the one of the wallet
+    WrongNetwork { expected: String, found: String },
+    /// the change address is a used address holding funds: the wallet sends
+    /// the change of every transaction to the same address
+    Reused { utxos: usize, amount: Lovelace },
+}
+
+/// check the address the wallet sends the change of the transactions to
+pub fn change_address_hygiene(snapshot: &WalletSnapshot) -> Result<ChangeAddressReport, ()> {
+    let address = &snapshot.change_address;
+    let mut issues = Vec::new();
+
+    match address {
+        Address::Byron(..) => issues.push(ChangeAddressIssue::Byron),
+        Address::Shelley(shelley) => match shelley.delegation() {
+            ShelleyDelegationPart::Null => issues.push(ChangeAddressIssue::Enterprise),
+            // pointer addresses are reported by their own check
+            ShelleyDelegationPart::Pointer(..) => (),
+            ShelleyDelegationPart::Key(..) | ShelleyDelegationPart::Script(..) => {
+                if let Ok(stake) = StakeAddress::try_from(shelley.clone()) {
+                    let stake = Address::Stake(stake);
+                    if !snapshot.reward_addresses.contains(&stake) {
+                        issues.push(ChangeAddressIssue::ForeignStakeKey {
+                            stake_address: stake.to_bech32().unwrap_or_else(|_| stake.to_hex()),
+                        });
+                    }
+                }
+            }
+        },
+        // a wallet returning a reward address as change address is broken,
+        // no transaction can pay to it
+        Address::Stake(..) => issues.push(ChangeAddressIssue::StakeAddress),
+    }
+
+    // the network id the wallet is connected to, the reward addresses come
+    // from the same wallet and cannot tell it is wrong
+    let expected = snapshot.network_id.as_ref().ok().map(|id| match id.0 {
+        0 => Network::Testnet,
+        1 => Network::Mainnet,
+        tag => Network::Other(tag),
+    });
+    if let (Some(expected), Some(found)) = (expected, address.network()) {
+        if expected != found {
+            issues.push(ChangeAddressIssue::WrongNetwork {
+                expected: network(expected),
+                found: network(found),
+            });
+        }
+    }
+
+    if snapshot.used_addresses.contains(address) {
+        let utxos: Vec<_> = snapshot
+            .utxos
+            .iter()
+            .filter(|utxo| {
+                utxo.address()
+                    .is_ok_and(|utxo_address| utxo_address == *address)
+            })
+            .collect();
+        if !utxos.is_empty() {
+            issues.push(ChangeAddressIssue::Reused {
+                utxos: utxos.len(),
+                amount: utxos.iter().map(|utxo| Lovelace::new(utxo.amount())).sum(),
+            });
+        }
+    }
+
+    Ok(ChangeAddressReport {
+        address: address.clone(),
+        issues,
+    })
+}
+
+fn network(network: Network) -> String {
+    match network {
+        Network::Mainnet => "mainnet".to_owned(),
+        Network::Testnet => "testnet".to_owned(),
+        Network::Other(tag) => format!("network {tag}"),
+    }
+}
+
+impl ChangeAddressIssue {
+    /// the issue as shown in the report, the amounts in ADA
+    ///
+    /// The [`Display`](fmt::Display) of the issue leaves the amounts out:
+    /// it identifies the finding in the history of the scans.
+    pub fn describe(&self, symbol: Symbol) -> String {
+        match self {
+            Self::Reused { utxos, amount } => format!(
+                "The change address is already used and holds {} on {utxos} UTxO(s): the wallet reuses the same address, linking all your transactions together",
+                amount.display(symbol)
+            ),
+            issue => issue.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ChangeAddressIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Byron => f.write_str(
+                "The change goes to a Byron address, which cannot be delegated",
+            ),
+            Self::Enterprise => f.write_str(
+                "The change goes to an address without stake key: it does not earn rewards",
+            ),
+            Self::StakeAddress => f.write_str(
+                "The change address is a reward address: no transaction can send the change to it",
+            ),
+            Self::ForeignStakeKey { stake_address } => write!(
+                f,
+                "The change delegates to {stake_address}, a stake key the wallet did not return"
+            ),
+            Self::WrongNetwork { expected, found } => write!(
+                f,
+                "The change address is on {found} while the wallet is on {expected}"
+            ),
+            Self::Reused { .. } => f.write_str(
+                "The change address is already used and holds funds: the wallet reuses the same address, linking all your transactions together",
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::snapshot::byron_leftovers;
+
+    /// the `byron` demo wallet, on mainnet, with one reward address
+    fn snapshot() -> WalletSnapshot {
+        WalletSnapshot::replay(&byron_leftovers())
+    }
+
+    fn address(bytes: &[u8]) -> Address {
+        Address::from_hex(&hex::encode(bytes)).expect("the address should decode")
+    }
+
+    /// the stake credential of the wallet
+    fn stake_key(snapshot: &WalletSnapshot) -> Vec<u8> {
+        let reward = hex::decode(snapshot.reward_addresses[0].to_hex()).expect("hex");
+        reward[1..].to_vec()
+    }
+
+    /// a base address delegating to the stake credential
+    fn base(header: u8, stake: &[u8]) -> Address {
+        address(&[&[header][..], &[0x77; 28][..], stake].concat())
+    }
+
+    fn issues(snapshot: &WalletSnapshot) -> Vec<ChangeAddressIssue> {
+        change_address_hygiene(snapshot)
+            .expect("the check should complete")
+            .issues
+    }
+
+    #[test]
+    fn fresh_address() {
+        let mut snapshot = snapshot();
+        snapshot.change_address = base(0x01, &stake_key(&snapshot));
+        assert_eq!(issues(&snapshot), vec![]);
+    }
+
+    #[test]
+    fn byron() {
+        let mut snapshot = snapshot();
+        snapshot.change_address = snapshot
+            .utxos
+            .iter()
+            .filter_map(|utxo| utxo.address().ok())
+            .find(|address| matches!(address, Address::Byron(..)))
+            .expect("the wallet holds Byron UTxOs");
+        assert!(issues(&snapshot).contains(&ChangeAddressIssue::Byron));
+    }
+
+    #[test]
+    fn enterprise() {
+        let mut snapshot = snapshot();
+        snapshot.change_address = address(&[&[0x61][..], &[0x77; 28][..]].concat());
+        assert_eq!(issues(&snapshot), vec![ChangeAddressIssue::Enterprise]);
+    }
+
+    #[test]
+    fn stake_address() {
+        let mut snapshot = snapshot();
+        snapshot.change_address = snapshot.reward_addresses[0].clone();
+        assert_eq!(issues(&snapshot), vec![ChangeAddressIssue::StakeAddress]);
+    }
+
+    #[test]
+    fn foreign_stake_key() {
+        let mut snapshot = snapshot();
+        snapshot.change_address = base(0x01, &[0x66; 28]);
+        let foreign = address(&[&[0xe1][..], &[0x66; 28][..]].concat());
+        assert_eq!(
+            issues(&snapshot),
+            vec![ChangeAddressIssue::ForeignStakeKey {
+                stake_address: foreign.to_bech32().expect("bech32"),
+            }]
+        );
+    }
+
+    #[test]
+    fn wrong_network() {
+        let mut snapshot = snapshot();
+        let stake_key = stake_key(&snapshot);
+        snapshot.change_address = base(0x00, &stake_key);
+        let wrong_network = ChangeAddressIssue::WrongNetwork {
+            expected: "mainnet".to_owned(),
+            found: "testnet".to_owned(),
+        };
+        assert!(issues(&snapshot).contains(&wrong_network));
+
+        // the reward address is on the same wrong network
+        snapshot.reward_addresses = vec![address(&[&[0xe0][..], &stake_key[..]].concat())];
+        assert_eq!(issues(&snapshot), vec![wrong_network]);
+
+        // nothing to compare with
+        snapshot.network_id = Err("getNetworkId failed".to_owned());
+        assert_eq!(issues(&snapshot), vec![]);
+    }
+
+    #[test]
+    fn reused() {
+        let mut snapshot = snapshot();
+        // the used address holding the 75 ₳ UTxO
+        snapshot.change_address = snapshot
+            .used_addresses
+            .iter()
+            .find(|address| {
+                snapshot
+                    .utxos
+                    .iter()
+                    .filter(|utxo| utxo.amount() == 75_000_000)
+                    .any(|utxo| {
+                        utxo.address()
+                            .is_ok_and(|utxo_address| utxo_address == **address)
+                    })
+            })
+            .cloned()
+            .expect("the wallet holds funds on a used address");
+
+        let issues = issues(&snapshot);
+        assert_eq!(
+            issues,
+            vec![ChangeAddressIssue::Reused {
+                utxos: 1,
+                amount: Lovelace::new(75_000_000),
+            }]
+        );
+        // the finding stays the same as the amount changes
+        assert!(!issues[0].to_string().contains("75"));
+    }
+}
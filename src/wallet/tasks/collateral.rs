@@ -0,0 +1,160 @@
+use super::credentials::{classify, Credential};
+use crate::wallet::{
+    lovelace::{Lovelace, Symbol},
+    output::decode_output,
+    protocol_parameters::ProtocolParameters,
+};
+use cardano_connector::Utxo;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// the amount of collateral requested to the wallet, CIP-30 wallets
+/// are expected to return collateral up to 5 ₳
+pub const COLLATERAL_AMOUNT: u64 = 5_000_000;
+
+/// collateral above this amount locks far more than any transaction needs
+const MAX_SENSIBLE_COLLATERAL: u64 = 4 * COLLATERAL_AMOUNT;
+
+#[derive(Clone, PartialEq)]
+pub struct CollateralReport {
+    pub collateral: Vec<Utxo>,
+    pub issues: Vec<CollateralIssue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CollateralIssue {
+    /// the wallet failed to return collateral
+    Unavailable { error: String },
+    /// the wallet has no collateral set
+    NotSet,
+    /// the collateral UTxO holds native assets
+    NotAdaOnly { utxo: String },
+    /// the collateral UTxO is not locked by a key hash
+    NotKeyHash { utxo: String },
+    /// the collateral UTxO is not in the UTxO set reported by the wallet
+    NotInUtxoSet { utxo: String },
+    /// more collateral inputs than the protocol allows
+    TooManyInputs { count: usize, max: u64 },
+    /// the collateral locks far more ADA than needed
+    TooLarge { total: Lovelace, balance: Lovelace },
+}
+
+/// check the collateral returned by the wallet
+pub fn collateral_health(
+    parameters: &ProtocolParameters,
+    collateral: Result<Vec<Utxo>, String>,
+    utxos: &[Utxo],
+) -> Result<CollateralReport, ()> {
+    let collateral = match collateral {
+        Ok(collateral) => collateral,
+        Err(error) => {
+            return Ok(CollateralReport {
+                collateral: Vec::new(),
+                issues: vec![CollateralIssue::Unavailable { error }],
+            })
+        }
+    };
+
+    let mut issues = Vec::new();
+
+    if collateral.is_empty() {
+        issues.push(CollateralIssue::NotSet);
+    }
+
+    for utxo in &collateral {
+        let id = format!("{}#{}", utxo.transaction_id(), utxo.index());
+
+        match decode_output(utxo) {
+            Ok(output) if output.value.has_assets() => {
+                issues.push(CollateralIssue::NotAdaOnly { utxo: id.clone() })
+            }
+            Ok(_) => (),
+            Err(error) => gloo::console::error!(error.to_string()),
+        }
+
+        // an address that cannot be decoded cannot be spent either
+        let key_hash = match utxo.address() {
+            Ok(address) => classify(&address).0 == Credential::KeyHash,
+            Err(error) => {
+                gloo::console::error!(error.to_string());
+                false
+            }
+        };
+        if !key_hash {
+            issues.push(CollateralIssue::NotKeyHash { utxo: id.clone() });
+        }
+
+        if !utxos.contains(utxo) {
+            issues.push(CollateralIssue::NotInUtxoSet { utxo: id });
+        }
+    }
+
+    if collateral.len() as u64 > parameters.max_collateral_inputs {
+        issues.push(CollateralIssue::TooManyInputs {
+            count: collateral.len(),
+            max: parameters.max_collateral_inputs,
+        });
+    }
+
+    let total: Lovelace = collateral
+        .iter()
+        .map(|utxo| Lovelace::new(utxo.amount()))
+        .sum();
+    let balance: Lovelace = utxos.iter().map(|utxo| Lovelace::new(utxo.amount())).sum();
+    if total > Lovelace::new(MAX_SENSIBLE_COLLATERAL)
+        || (utxos.len() > 1 && total >= balance && total > Lovelace::ZERO)
+    {
+        issues.push(CollateralIssue::TooLarge { total, balance });
+    }
+
+    Ok(CollateralReport { collateral, issues })
+}
+
+impl CollateralIssue {
+    /// the issue as shown in the report, the amounts in ADA
+    ///
+    /// The [`Display`](fmt::Display) of the issue leaves the amounts out:
+    /// it identifies the finding in the history of the scans.
+    pub fn describe(&self, symbol: Symbol) -> String {
+        match self {
+            Self::TooLarge { total, balance } => format!(
+                "The collateral locks {} (out of a balance of {}), far more than needed",
+                total.display(symbol),
+                balance.display(symbol)
+            ),
+            issue => issue.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for CollateralIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unavailable { error } => {
+                write!(f, "The wallet failed to return the collateral: {error}")
+            }
+            Self::NotSet => f.write_str(
+                "The wallet has no collateral set: you won't be able to interact with smart contracts",
+            ),
+            Self::NotAdaOnly { utxo } => write!(
+                f,
+                "{utxo} holds native assets, collateral UTxOs should only hold ADA"
+            ),
+            Self::NotKeyHash { utxo } => write!(
+                f,
+                "{utxo} is not locked by a key hash, it cannot be used as collateral"
+            ),
+            Self::NotInUtxoSet { utxo } => write!(
+                f,
+                "{utxo} is not one of the UTxOs reported by the wallet"
+            ),
+            Self::TooManyInputs { count, max } => write!(
+                f,
+                "{count} collateral UTxOs, the protocol allows at most {max}"
+            ),
+            Self::TooLarge { .. } => {
+                f.write_str("The collateral locks far more ADA than needed")
+            }
+        }
+    }
+}
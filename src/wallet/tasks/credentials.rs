@@ -0,0 +1,76 @@
+use super::decode_address;
+use cardano_connector::{Address, Utxo};
+use pallas_addresses::{ShelleyDelegationPart, ShelleyPaymentPart, StakePayload};
+use std::{collections::HashMap, fmt};
+
+/// the kind of credential found in the payment or the stake part of an address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Credential {
+    /// a hash of a verification key, spending requires a signature
+    KeyHash,
+    /// a hash of a script, spending requires the script and its witnesses
+    ScriptHash,
+    /// a pointer to a stake registration certificate
+    Pointer,
+    /// no credential (enterprise addresses, Byron addresses' stake part)
+    None,
+}
+
+/// classify the payment and stake credentials of the given address
+pub fn classify(address: &Address) -> (Credential, Credential) {
+    match address {
+        // Byron addresses are bootstrap key addresses without stake part
+        Address::Byron(..) => (Credential::KeyHash, Credential::None),
+        Address::Shelley(address) => {
+            let payment = match address.payment() {
+                ShelleyPaymentPart::Key(..) => Credential::KeyHash,
+                ShelleyPaymentPart::Script(..) => Credential::ScriptHash,
+            };
+            let stake = match address.delegation() {
+                ShelleyDelegationPart::Key(..) => Credential::KeyHash,
+                ShelleyDelegationPart::Script(..) => Credential::ScriptHash,
+                ShelleyDelegationPart::Pointer(..) => Credential::Pointer,
+                ShelleyDelegationPart::Null => Credential::None,
+            };
+            (payment, stake)
+        }
+        Address::Stake(address) => match address.payload() {
+            StakePayload::Stake(..) => (Credential::None, Credential::KeyHash),
+            StakePayload::Script(..) => (Credential::None, Credential::ScriptHash),
+        },
+    }
+}
+
+/// list the UTxOs with a script hash in their payment or stake credential
+///
+/// Wallets are expected to return UTxOs they can spend with their own keys.
+/// A script payment credential means the UTxO cannot be spent without
+/// providing the script and extra witnesses.
+pub fn script_credentials(utxos: &[Utxo]) -> Result<HashMap<Address, Vec<Utxo>>, ()> {
+    let mut found = HashMap::new();
+
+    for utxo in utxos {
+        let address = decode_address!(utxo.address());
+
+        let (payment, stake) = classify(&address);
+        if payment == Credential::ScriptHash || stake == Credential::ScriptHash {
+            found
+                .entry(address)
+                .or_insert_with(Vec::new)
+                .push(utxo.clone());
+        }
+    }
+
+    Ok(found)
+}
+
+impl fmt::Display for Credential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KeyHash => f.write_str("key hash"),
+            Self::ScriptHash => f.write_str("script hash"),
+            Self::Pointer => f.write_str("pointer"),
+            Self::None => f.write_str("none"),
+        }
+    }
+}
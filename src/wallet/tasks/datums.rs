@@ -0,0 +1,27 @@
+use crate::wallet::output::{decode_output, Output};
+use cardano_connector::Utxo;
+
+/// list the UTxOs carrying a datum or a reference script
+///
+/// UTxOs at ordinary wallet addresses don't need a datum nor a reference
+/// script. These inflate the minimum ADA of the UTxO and are often leftovers
+/// of dApp interactions, or of attacks.
+pub fn datums_and_scripts(utxos: &[Utxo]) -> Result<Vec<(Utxo, Output)>, ()> {
+    let mut found = Vec::new();
+
+    for utxo in utxos {
+        let output = match decode_output(utxo) {
+            Ok(output) => output,
+            Err(error) => {
+                gloo::console::error!(error.to_string());
+                continue;
+            }
+        };
+
+        if output.datum.is_some() || output.script_ref.is_some() {
+            found.push((utxo.clone(), output));
+        }
+    }
+
+    Ok(found)
+}
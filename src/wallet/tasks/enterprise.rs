@@ -0,0 +1,34 @@
+use super::decode_address;
+use cardano_connector::{Address, Utxo};
+use pallas_addresses::ShelleyDelegationPart;
+use std::collections::HashMap;
+
+/// list the UTxOs held on enterprise addresses
+///
+/// Enterprise addresses don't have a delegation part: the funds they hold
+/// cannot be delegated and don't participate in staking or governance.
+pub fn does_contain_enterprise_addresses(
+    utxos: &[Utxo],
+) -> Result<HashMap<Address, Vec<Utxo>>, ()> {
+    let mut found = HashMap::new();
+
+    for utxo in utxos {
+        let address = decode_address!(utxo.address());
+
+        let is_enterprise = match &address {
+            Address::Shelley(address) => {
+                matches!(address.delegation(), ShelleyDelegationPart::Null)
+            }
+            Address::Byron(..) | Address::Stake(..) => false,
+        };
+
+        if is_enterprise {
+            found
+                .entry(address)
+                .or_insert_with(Vec::new)
+                .push(utxo.clone());
+        }
+    }
+
+    Ok(found)
+}
@@ -1,64 +1,186 @@
+pub mod accounts;
 pub mod byron;
+pub mod change_address;
+pub mod collateral;
+pub mod credentials;
+pub mod datums;
+pub mod enterprise;
+pub mod pointer;
+pub mod spam;
 pub mod stake;
 pub mod used_addresses;
 
-use cardano_connector::{error::APIError, Address, ConnectedWallet, Utxo};
-use std::{
-    collections::{HashMap, HashSet},
-    sync::Arc,
+use super::{
+    api::ApiError, lovelace::Symbol, output::Output, protocol_parameters::ProtocolParameters,
+    snapshot::WalletSnapshot, token_lists::TokenLists,
 };
+use cardano_connector::{Address, Utxo};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
 pub enum AlertMessage {
-    UTxOsWithByronAddress { utxos: HashMap<Address, Vec<Utxo>> },
-    UTxOsWithUnknownRewardAddresses { utxos: HashMap<Address, Vec<Utxo>> },
-    UTxOsWithUsedAddresses { utxos: HashMap<Address, Vec<Utxo>> },
-    APIError { error: APIError },
+    UTxOsWithByronAddress {
+        utxos: HashMap<Address, Vec<Utxo>>,
+    },
+    UTxOsWithUnknownRewardAddresses {
+        utxos: HashMap<Address, Vec<Utxo>>,
+    },
+    UTxOsWithUsedAddresses {
+        utxos: HashMap<Address, Vec<Utxo>>,
+    },
+    UTxOsWithPointerAddresses {
+        utxos: HashMap<Address, Vec<Utxo>>,
+    },
+    UTxOsWithEnterpriseAddresses {
+        utxos: HashMap<Address, Vec<Utxo>>,
+    },
+    UTxOsWithScriptCredentials {
+        utxos: HashMap<Address, Vec<Utxo>>,
+    },
+    SuspiciousUTxOs {
+        utxos: Vec<spam::SuspiciousUtxo>,
+    },
+    UTxOsWithDatumsOrScripts {
+        utxos: Vec<(Utxo, Output)>,
+    },
+    Collateral {
+        report: collateral::CollateralReport,
+    },
+    Accounts {
+        report: accounts::AccountsReport,
+    },
+    ChangeAddress {
+        report: change_address::ChangeAddressReport,
+    },
+    Network {
+        symbol: Symbol,
+    },
+    WalletAddresses {
+        addresses: HashSet<Address>,
+    },
+    APIError {
+        error: ApiError,
+    },
 }
 
-pub async fn analyse_wallet(wallet: ConnectedWallet, alert: impl Fn(AlertMessage)) {
-    let utxos = Arc::new(need!(wallet.all_utxos(None).await, alert));
-
-    let () = byron::does_contain_byron_addresses(&utxos)
-        .and_then(|utxos| Ok(alert(AlertMessage::UTxOsWithByronAddress { utxos })))
-        .unwrap_or_else(|()| todo!());
-
-    let change_address = need!(wallet.change_address().await, alert);
-
-    let used_addresses = need!(wallet.used_addresses(None).await, alert);
-    let used_addresses = used_addresses.into_iter().collect::<HashSet<_>>();
-    let used_addresses = Arc::new(used_addresses);
-
-    let unused_addresses = need!(wallet.unused_addresses().await, alert);
-    let unused_addresses = unused_addresses.into_iter().collect::<HashSet<_>>();
-    let unused_addresses = Arc::new(unused_addresses);
+/// the checks run over the snapshot of the wallet, in the worker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Check {
+    ByronAddresses,
+    EnterpriseAddresses,
+    ScriptCredentials,
+    DatumsAndScripts,
+    Collateral,
+    PointerAddresses,
+    Spam,
+    RewardKeys,
+    Accounts,
+    UsedAddresses,
+    ChangeAddress,
+}
 
-    let reward_addresses = need!(wallet.reward_addresses().await, alert);
-    let reward_addresses = reward_addresses.into_iter().collect::<HashSet<_>>();
-    let reward_addresses = Arc::new(reward_addresses);
+impl Check {
+    /// in the order they are run
+    pub const ALL: [Self; 11] = [
+        Self::ByronAddresses,
+        Self::EnterpriseAddresses,
+        Self::ScriptCredentials,
+        Self::DatumsAndScripts,
+        Self::Collateral,
+        Self::PointerAddresses,
+        Self::Spam,
+        Self::RewardKeys,
+        Self::Accounts,
+        Self::UsedAddresses,
+        Self::ChangeAddress,
+    ];
 
-    let () = stake::consistent_reward_keys(&reward_addresses, &utxos)
-        .and_then(|utxos| {
-            Ok(alert(AlertMessage::UTxOsWithUnknownRewardAddresses {
-                utxos,
-            }))
-        })
-        .unwrap_or_else(|()| todo!());
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::ByronAddresses => "Byron addresses",
+            Self::EnterpriseAddresses => "Enterprise addresses",
+            Self::ScriptCredentials => "Script credentials",
+            Self::DatumsAndScripts => "Datums and scripts",
+            Self::Collateral => "Collateral",
+            Self::PointerAddresses => "Pointer addresses",
+            Self::Spam => "Spam tokens and unlisted addresses",
+            Self::RewardKeys => "Reward keys",
+            Self::Accounts => "Accounts",
+            Self::UsedAddresses => "Used addresses",
+            Self::ChangeAddress => "Change address",
+        }
+    }
 
-    let () = used_addresses::no_used_addresses(&used_addresses, &utxos)
-        .and_then(|utxos| Ok(alert(AlertMessage::UTxOsWithUsedAddresses { utxos })))
-        .unwrap_or_else(|()| todo!());
-}
+    /// run the check over the snapshot of the wallet
+    ///
+    /// The local storage is not available in the worker: the protocol
+    /// parameters and the token lists are given by the page.
+    pub fn run(
+        self,
+        snapshot: &WalletSnapshot,
+        parameters: &ProtocolParameters,
+        token_lists: &TokenLists,
+    ) -> Result<AlertMessage, ()> {
+        let utxos = &snapshot.utxos;
 
-macro_rules! need {
-    ($QUERY:expr, $ALERT:ident) => {{
-        match $QUERY {
-            Ok(res) => res,
-            Err(error) => {
-                $ALERT(AlertMessage::APIError { error });
-                return;
+        match self {
+            Self::ByronAddresses => byron::does_contain_byron_addresses(utxos)
+                .map(|utxos| AlertMessage::UTxOsWithByronAddress { utxos }),
+            Self::EnterpriseAddresses => enterprise::does_contain_enterprise_addresses(utxos)
+                .map(|utxos| AlertMessage::UTxOsWithEnterpriseAddresses { utxos }),
+            Self::ScriptCredentials => credentials::script_credentials(utxos)
+                .map(|utxos| AlertMessage::UTxOsWithScriptCredentials { utxos }),
+            Self::DatumsAndScripts => datums::datums_and_scripts(utxos)
+                .map(|utxos| AlertMessage::UTxOsWithDatumsOrScripts { utxos }),
+            Self::Collateral => {
+                collateral::collateral_health(parameters, snapshot.collateral.clone(), utxos)
+                    .map(|report| AlertMessage::Collateral { report })
             }
+            Self::PointerAddresses => {
+                pointer::does_contain_pointer_addresses(&snapshot.wallet_addresses(), utxos)
+                    .map(|utxos| AlertMessage::UTxOsWithPointerAddresses { utxos })
+            }
+            Self::Spam => spam::spam_and_dust(token_lists, &snapshot.wallet_addresses(), utxos)
+                .map(|utxos| AlertMessage::SuspiciousUTxOs { utxos }),
+            Self::RewardKeys => {
+                let reward_addresses: HashSet<_> =
+                    snapshot.reward_addresses.iter().cloned().collect();
+                stake::consistent_reward_keys(&reward_addresses, utxos)
+                    .map(|utxos| AlertMessage::UTxOsWithUnknownRewardAddresses { utxos })
+            }
+            Self::Accounts => {
+                accounts::accounts(snapshot).map(|report| AlertMessage::Accounts { report })
+            }
+            Self::UsedAddresses => {
+                let used_addresses: HashSet<_> = snapshot.used_addresses.iter().cloned().collect();
+                used_addresses::no_used_addresses(&used_addresses, utxos)
+                    .map(|utxos| AlertMessage::UTxOsWithUsedAddresses { utxos })
+            }
+            Self::ChangeAddress => change_address::change_address_hygiene(snapshot)
+                .map(|report| AlertMessage::ChangeAddress { report }),
         }
-    }};
+    }
+}
+
+/// what the page learns from the snapshot without running the checks
+pub fn wallet_details(snapshot: &WalletSnapshot, alert: impl Fn(AlertMessage)) {
+    // the network id is the one the wallet is connected to, the network
+    // tag of the change address is only used when the call failed
+    let symbol = match &snapshot.network_id {
+        Ok(network_id) => Some(Symbol::from(*network_id)),
+        Err(_) => snapshot.change_address.network().map(Symbol::from),
+    };
+    if let Some(symbol) = symbol {
+        alert(AlertMessage::Network { symbol });
+    }
+
+    alert(AlertMessage::WalletAddresses {
+        addresses: snapshot
+            .wallet_addresses()
+            .into_iter()
+            .chain(snapshot.reward_addresses.iter().cloned())
+            .collect(),
+    });
 }
 
 macro_rules! decode_address {
@@ -75,4 +197,3 @@ macro_rules! decode_address {
 }
 
 pub(self) use decode_address;
-pub(self) use need;
@@ -0,0 +1,42 @@
+use super::decode_address;
+use cardano_connector::{Address, Utxo};
+use pallas_addresses::ShelleyDelegationPart;
+use std::collections::{HashMap, HashSet};
+
+/// list the pointer addresses known by the wallet and the UTxOs they hold
+///
+/// Since the Conway era pointer addresses no longer delegate stake: the funds
+/// held on them don't earn rewards anymore. The addresses advertised by the
+/// wallet (used, unused or change addresses) are reported too, even if they
+/// don't hold any UTxO, as the wallet may still send funds to them.
+pub fn does_contain_pointer_addresses(
+    addresses: &HashSet<Address>,
+    utxos: &[Utxo],
+) -> Result<HashMap<Address, Vec<Utxo>>, ()> {
+    let mut found: HashMap<Address, Vec<Utxo>> = HashMap::new();
+
+    for address in addresses {
+        if is_pointer_address(address) {
+            found.entry(address.clone()).or_default();
+        }
+    }
+
+    for utxo in utxos {
+        let address = decode_address!(utxo.address());
+
+        if is_pointer_address(&address) {
+            found.entry(address).or_default().push(utxo.clone());
+        }
+    }
+
+    Ok(found)
+}
+
+fn is_pointer_address(address: &Address) -> bool {
+    match address {
+        Address::Shelley(address) => {
+            matches!(address.delegation(), ShelleyDelegationPart::Pointer(..))
+        }
+        Address::Byron(..) | Address::Stake(..) => false,
+    }
+}
@@ -0,0 +1,209 @@
+use crate::wallet::{
+    lovelace::{Lovelace, Symbol},
+    output::{decode_output, AssetName, PolicyId},
+    token_lists::TokenLists,
+};
+use cardano_connector::{Address, Utxo};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fmt};
+
+/// the ADA only UTxOs holding less lovelace than this are reported when
+/// their address is not listed by the wallet
+const SMALL_UTXO_THRESHOLD: u64 = 1_000_000;
+
+/// an unsolicited airdrop usually comes with the minimum ADA only
+const AIRDROP_THRESHOLD: u64 = 2_000_000;
+
+#[derive(Clone, PartialEq)]
+pub struct SuspiciousUtxo {
+    pub utxo: Utxo,
+    pub reasons: Vec<SpamReason>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpamReason {
+    /// the token is in the deny list
+    DeniedToken {
+        policy_id: PolicyId,
+        name: AssetName,
+    },
+    /// the token arrived on its own, with the minimum ADA
+    UnsolicitedToken {
+        policy_id: PolicyId,
+        name: AssetName,
+    },
+    /// the asset name contains a link
+    UrlInName { name: AssetName },
+    /// the asset name contains wording commonly used by phishing tokens
+    SuspiciousWording { name: AssetName, word: String },
+    /// the asset name contains characters mimicking latin letters
+    Homoglyphs { name: AssetName },
+    /// a small ADA only UTxO at an address the wallet does not list in its
+    /// used, unused or change addresses
+    ///
+    /// The sender of the UTxO is not known: the wallet may hold it at an
+    /// address it forgot to return, or on an account it does not report.
+    UnlistedAddress { lovelace: Lovelace },
+}
+
+/// search for UTxOs carrying spam tokens, or small UTxOs at addresses the
+/// wallet does not list
+///
+/// The `wallet_addresses` are the addresses reported by the wallet (used,
+/// unused and change addresses). Tokens from the allow list are never
+/// reported, tokens from the deny list always are.
+pub fn spam_and_dust(
+    token_lists: &TokenLists,
+    wallet_addresses: &HashSet<Address>,
+    utxos: &[Utxo],
+) -> Result<Vec<SuspiciousUtxo>, ()> {
+    let mut found = Vec::new();
+
+    for utxo in utxos {
+        let output = match decode_output(utxo) {
+            Ok(output) => output,
+            Err(error) => {
+                gloo::console::error!(error.to_string());
+                continue;
+            }
+        };
+        let value = &output.value;
+
+        let mut reasons = Vec::new();
+
+        let unknown_assets = value
+            .assets()
+            .filter(|(policy_id, name, _)| !token_lists.is_allowed(policy_id, name))
+            .collect::<Vec<_>>();
+        let unsolicited = !unknown_assets.is_empty()
+            && unknown_assets.len() == value.num_assets()
+            && value.coin <= AIRDROP_THRESHOLD;
+
+        for (policy_id, name, _) in unknown_assets {
+            if token_lists.is_denied(policy_id, name) {
+                reasons.push(SpamReason::DeniedToken {
+                    policy_id: policy_id.clone(),
+                    name: name.clone(),
+                });
+            } else if unsolicited {
+                reasons.push(SpamReason::UnsolicitedToken {
+                    policy_id: policy_id.clone(),
+                    name: name.clone(),
+                });
+            }
+
+            let Some(text) = name.as_utf8() else {
+                continue;
+            };
+            let lowercase = text.to_lowercase();
+
+            if contains_url(&lowercase) {
+                reasons.push(SpamReason::UrlInName { name: name.clone() });
+            }
+            if let Some(word) = token_lists
+                .suspicious_words
+                .iter()
+                .find(|word| lowercase.contains(word.as_str()))
+            {
+                reasons.push(SpamReason::SuspiciousWording {
+                    name: name.clone(),
+                    word: word.clone(),
+                });
+            }
+            if contains_homoglyphs(text) {
+                reasons.push(SpamReason::Homoglyphs { name: name.clone() });
+            }
+        }
+
+        if !value.has_assets() && value.coin < SMALL_UTXO_THRESHOLD {
+            let known = utxo
+                .address()
+                .map(|address| wallet_addresses.contains(&address))
+                .unwrap_or_default();
+            if !known {
+                reasons.push(SpamReason::UnlistedAddress {
+                    lovelace: Lovelace::new(value.coin),
+                });
+            }
+        }
+
+        if !reasons.is_empty() {
+            found.push(SuspiciousUtxo {
+                utxo: utxo.clone(),
+                reasons,
+            });
+        }
+    }
+
+    Ok(found)
+}
+
+fn contains_url(name: &str) -> bool {
+    const PATTERNS: &[&str] = &[
+        "://", "www.", "http", ".com", ".io", ".net", ".org", ".xyz", ".app", ".site", "t.me/",
+    ];
+
+    PATTERNS.iter().any(|pattern| name.contains(pattern))
+}
+
+/// detect the characters commonly used to impersonate latin letters:
+/// cyrillic and greek look-alikes, full width forms and invisible characters
+fn contains_homoglyphs(name: &str) -> bool {
+    const CONFUSABLES: &[char] = &[
+        // cyrillic
+        'а', 'в', 'е', 'к', 'м', 'н', 'о', 'р', 'с', 'т', 'у', 'х', 'ѕ', 'і', 'ј', 'ԁ', 'ԛ', 'ԝ',
+        'А', 'В', 'Е', 'К', 'М', 'Н', 'О', 'Р', 'С', 'Т', 'Х', 'Ѕ', 'І', 'Ј', // greek
+        'α', 'ο', 'ν', 'ρ', 'τ', 'υ', 'ι', 'κ', 'Α', 'Β', 'Ε', 'Ζ', 'Η', 'Ι', 'Κ', 'Μ', 'Ν', 'Ο',
+        'Ρ', 'Τ', 'Υ', 'Χ', // latin look-alikes
+        'ɑ', 'ɡ', 'ı', 'ȷ', 'ℓ',
+    ];
+
+    let has_latin = name.chars().any(|c| c.is_ascii_alphabetic());
+
+    name.chars().any(|c| {
+        let full_width = ('\u{FF01}'..='\u{FF5E}').contains(&c);
+        let invisible = matches!(c, '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}');
+
+        full_width || invisible || (has_latin && CONFUSABLES.contains(&c))
+    })
+}
+
+impl SpamReason {
+    /// the reason as shown in the report, the amounts in ADA
+    ///
+    /// The [`Display`](fmt::Display) of the reason leaves the amounts out:
+    /// it identifies the finding in the history of the scans.
+    pub fn describe(&self, symbol: Symbol) -> String {
+        match self {
+            Self::UnlistedAddress { lovelace } => format!(
+                "Small UTxO of {} at an address the wallet does not list",
+                lovelace.display(symbol)
+            ),
+            reason => reason.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SpamReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DeniedToken { policy_id, name } => {
+                write!(f, "{name} ({policy_id}) is in the deny list")
+            }
+            Self::UnsolicitedToken { policy_id, name } => {
+                write!(
+                    f,
+                    "{name} ({policy_id}) was received on its own with the minimum ADA"
+                )
+            }
+            Self::UrlInName { name } => write!(f, "{name} contains a link"),
+            Self::SuspiciousWording { name, word } => write!(f, "{name} contains \"{word}\""),
+            Self::Homoglyphs { name } => {
+                write!(f, "{name} contains characters mimicking latin letters")
+            }
+            Self::UnlistedAddress { .. } => {
+                f.write_str("Small UTxO at an address the wallet does not list")
+            }
+        }
+    }
+}
@@ -2,7 +2,12 @@ use std::collections::{HashMap, HashSet};
 
 use super::decode_address;
 use cardano_connector::{Address, Utxo};
+use pallas_addresses::ShelleyDelegationPart;
 
+/// the UTxOs delegating to a stake key the wallet did not return
+///
+/// A wallet returning several reward addresses is reported by the
+/// [`accounts`](super::accounts) check.
 pub fn consistent_reward_keys(
     reward_addresses: &HashSet<Address>,
     utxos: &[Utxo],
@@ -21,6 +26,15 @@ pub fn consistent_reward_keys(
             Address::Byron(..) => continue,
 
             Address::Shelley(address) => {
+                // enterprise and pointer addresses don't have a stake key,
+                // they are reported by their own checks
+                if matches!(
+                    address.delegation(),
+                    ShelleyDelegationPart::Null | ShelleyDelegationPart::Pointer(..)
+                ) {
+                    continue;
+                }
+
                 let stake_address = match address.try_into() {
                     Ok(addr) => Address::Stake(addr),
                     Err(error) => {
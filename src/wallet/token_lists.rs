@@ -0,0 +1,84 @@
+//! lists of known good and known bad native assets
+//!
+//! A list is bundled with the application (`assets/token-lists.json`) and
+//! can be extended by the user with their own file. The user's lists are
+//! kept in the browser's local storage. Nothing is fetched from an online
+//! service.
+//!
+//! Entries of the `allow` and `deny` lists are either a policy id (all the
+//! assets of the policy) or a `<policy id>.<asset name hex>`. They are
+//! lowercased when loaded, like the hex of the assets they are compared to.
+
+use super::output::{AssetName, PolicyId};
+use gloo::storage::{errors::StorageError, LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+const BUNDLED: &str = include_str!("../../assets/token-lists.json");
+const STORAGE_KEY: &str = "cardano-web-wallet-analyser.token-lists";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenLists {
+    #[serde(default)]
+    pub allow: BTreeSet<String>,
+    #[serde(default)]
+    pub deny: BTreeSet<String>,
+    /// words that are commonly found in the name of phishing tokens
+    #[serde(default)]
+    pub suspicious_words: BTreeSet<String>,
+}
+
+impl TokenLists {
+    pub fn bundled() -> Self {
+        let mut lists = Self::default();
+        lists.extend(
+            serde_json::from_str(BUNDLED).expect("the bundled token lists should be valid"),
+        );
+        lists
+    }
+
+    /// the lists previously added by the user
+    pub fn user() -> Self {
+        LocalStorage::get(STORAGE_KEY).unwrap_or_default()
+    }
+
+    /// the bundled lists extended with the user's lists
+    pub fn load() -> Self {
+        let mut lists = Self::bundled();
+        lists.extend(Self::user());
+        lists
+    }
+
+    /// add the given lists to the user's lists
+    pub fn save_user(lists: Self) -> Result<(), StorageError> {
+        let mut user = Self::user();
+        user.extend(lists);
+        LocalStorage::set(STORAGE_KEY, user)
+    }
+
+    pub fn extend(&mut self, other: Self) {
+        self.allow
+            .extend(other.allow.into_iter().map(|entry| entry.to_lowercase()));
+        self.deny
+            .extend(other.deny.into_iter().map(|entry| entry.to_lowercase()));
+        self.suspicious_words.extend(
+            other
+                .suspicious_words
+                .into_iter()
+                .map(|word| word.to_lowercase()),
+        );
+    }
+
+    pub fn is_allowed(&self, policy_id: &PolicyId, name: &AssetName) -> bool {
+        Self::contains(&self.allow, policy_id, name)
+    }
+
+    pub fn is_denied(&self, policy_id: &PolicyId, name: &AssetName) -> bool {
+        Self::contains(&self.deny, policy_id, name)
+    }
+
+    fn contains(list: &BTreeSet<String>, policy_id: &PolicyId, name: &AssetName) -> bool {
+        let policy_id = policy_id.to_string();
+        list.contains(&policy_id) || list.contains(&format!("{policy_id}.{}", name.to_hex()))
+    }
+}
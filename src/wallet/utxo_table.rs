@@ -0,0 +1,440 @@
+use super::{
+    dom_scope::DomScope,
+    lovelace::{Lovelace, ParseLovelaceError, Symbol},
+    output::Output,
+    protocol_parameters::ProtocolParameters,
+    utxos::DecodedUtxos,
+};
+use cardano_connector::Utxo;
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, collections::HashMap, rc::Rc};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+const PAGE_SIZE: usize = 50;
+
+/// the findings of the checks mentioning a UTxO, indexed by [`utxo_id`]
+pub type UtxoFindings = HashMap<String, Vec<String>>;
+
+/// identify a UTxO as `<transaction id>#<output index>`
+pub fn utxo_id(utxo: &Utxo) -> String {
+    format!("{}#{}", utxo.transaction_id(), utxo.index())
+}
+
+/// a sortable, filterable and paginated table of the UTxOs
+pub struct UtxoTable {
+    sort: (Column, bool),
+    filters: Filters,
+    page: usize,
+    selected: Option<usize>,
+    /// the indices of the rows matching the filters, in the sort order
+    visible: Vec<usize>,
+}
+
+/// the displayed values of a UTxO, computed once by the analysis worker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UtxoRow {
+    id: String,
+    tx_hash: String,
+    index: u64,
+    amount: Lovelace,
+    num_assets: usize,
+    /// size of the output, to compute its minimum ADA with the protocol
+    /// parameters of the page
+    size: Option<usize>,
+    address: String,
+}
+
+#[derive(Default)]
+struct Filters {
+    search: String,
+    address: String,
+    min_amount: AmountFilter,
+    max_amount: AmountFilter,
+    flagged: bool,
+    has_tokens: bool,
+}
+
+/// an amount of ADA typed in a filter, `Err` if it does not parse: the
+/// filter is then ignored and its input shown as invalid
+#[derive(Default)]
+struct AmountFilter(Option<Result<Lovelace, ParseLovelaceError>>);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    TxHash,
+    Amount,
+    Assets,
+    MinAda,
+    Address,
+}
+
+pub enum Filter {
+    Search(String),
+    Address(String),
+    MinAmount(String),
+    MaxAmount(String),
+    Flagged(bool),
+    HasTokens(bool),
+}
+
+pub enum Message {
+    Sort(Column),
+    Filter(Filter),
+    Page(usize),
+    Select(usize),
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    pub decoded: Rc<DecodedUtxos>,
+    pub parameters: ProtocolParameters,
+    pub findings: Rc<UtxoFindings>,
+    #[prop_or_default]
+    pub symbol: Symbol,
+}
+
+impl Component for UtxoTable {
+    type Message = Message;
+    type Properties = Properties;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let mut table = Self {
+            sort: (Column::Amount, false),
+            filters: Filters::default(),
+            page: 0,
+            selected: None,
+            visible: Vec::new(),
+        };
+        table.refresh(ctx.props());
+        table
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        let props = ctx.props();
+        // the findings and the parameters only change the badges and the
+        // minimum ADA, the selection and the page are kept
+        if !Rc::ptr_eq(&props.decoded, &old_props.decoded) {
+            self.selected = None;
+            self.page = 0;
+        }
+        // the flagged filter depends on the findings
+        if !Rc::ptr_eq(&props.decoded, &old_props.decoded)
+            || !Rc::ptr_eq(&props.findings, &old_props.findings)
+        {
+            self.refresh(props);
+        }
+        props != old_props
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::Sort(column) => {
+                self.sort = if self.sort.0 == column {
+                    (column, !self.sort.1)
+                } else {
+                    (column, true)
+                };
+                self.refresh(ctx.props());
+            }
+            Message::Filter(filter) => {
+                match filter {
+                    Filter::Search(search) => self.filters.search = search.to_lowercase(),
+                    Filter::Address(address) => self.filters.address = address.to_lowercase(),
+                    Filter::MinAmount(amount) => {
+                        self.filters.min_amount = AmountFilter::new(&amount)
+                    }
+                    Filter::MaxAmount(amount) => {
+                        self.filters.max_amount = AmountFilter::new(&amount)
+                    }
+                    Filter::Flagged(flagged) => self.filters.flagged = flagged,
+                    Filter::HasTokens(has_tokens) => self.filters.has_tokens = has_tokens,
+                }
+                self.page = 0;
+                self.refresh(ctx.props());
+            }
+            Message::Page(page) => self.page = page,
+            Message::Select(index) => {
+                self.selected = if self.selected == Some(index) {
+                    None
+                } else {
+                    Some(index)
+                };
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let Properties {
+            decoded,
+            parameters,
+            findings,
+            symbol,
+        } = ctx.props();
+        let symbol = *symbol;
+        let link = ctx.link();
+        let scope = DomScope::of(ctx);
+
+        let rows = &decoded.rows;
+        let visible = &self.visible;
+        let num_pages = visible.len().div_ceil(PAGE_SIZE).max(1);
+        let page = self.page.min(num_pages - 1);
+
+        let header = |column: Column, label: &'static str| {
+            let arrow = match self.sort {
+                (sorted, true) if sorted == column => " ▲",
+                (sorted, false) if sorted == column => " ▼",
+                _ => "",
+            };
+            html! {
+                <th role="button" onclick={link.callback(move |_| Message::Sort(column))}>
+                    {format!("{label}{arrow}")}
+                </th>
+            }
+        };
+
+        let text_filter = |filter: fn(String) -> Filter| {
+            link.callback(move |event: InputEvent| {
+                let input: HtmlInputElement = event.target_unchecked_into();
+                Message::Filter(filter(input.value()))
+            })
+        };
+        let check_filter = |filter: fn(bool) -> Filter| {
+            link.callback(move |event: Event| {
+                let input: HtmlInputElement = event.target_unchecked_into();
+                Message::Filter(filter(input.checked()))
+            })
+        };
+
+        html! {
+            <>
+                <div class="row g-2 mb-3">
+                    <div class="col-md-4">
+                        <input type="search" class="form-control form-control-sm" placeholder="Search TX hash or address" oninput={text_filter(Filter::Search)} />
+                    </div>
+                    <div class="col-md-3">
+                        <input type="search" class="form-control form-control-sm" placeholder="Address contains" oninput={text_filter(Filter::Address)} />
+                    </div>
+                    <div class="col-md-1">
+                        <input type="text" class={classes!("form-control", "form-control-sm", self.filters.min_amount.is_invalid().then_some("is-invalid"))} placeholder={format!("Min {symbol}")} title={self.filters.min_amount.is_invalid().then_some("Not a valid ADA amount")} oninput={text_filter(Filter::MinAmount)} />
+                    </div>
+                    <div class="col-md-1">
+                        <input type="text" class={classes!("form-control", "form-control-sm", self.filters.max_amount.is_invalid().then_some("is-invalid"))} placeholder={format!("Max {symbol}")} title={self.filters.max_amount.is_invalid().then_some("Not a valid ADA amount")} oninput={text_filter(Filter::MaxAmount)} />
+                    </div>
+                    <div class="col-md-3 d-flex align-items-center">
+                        <div class="form-check form-check-inline">
+                            <input class="form-check-input" type="checkbox" id={scope.id("utxo-filter-flagged")} onchange={check_filter(Filter::Flagged)} />
+                            <label class="form-check-label small" for={scope.id("utxo-filter-flagged")}>{"Flagged"}</label>
+                        </div>
+                        <div class="form-check form-check-inline">
+                            <input class="form-check-input" type="checkbox" id={scope.id("utxo-filter-tokens")} onchange={check_filter(Filter::HasTokens)} />
+                            <label class="form-check-label small" for={scope.id("utxo-filter-tokens")}>{"Has tokens"}</label>
+                        </div>
+                    </div>
+                </div>
+                <div class="table-responsive">
+                    <table class="table table-hover">
+                        <thead>
+                            <tr>
+                                {header(Column::TxHash, "TX Hash")}
+                                <th>{"Output Index"}</th>
+                                {header(Column::Amount, "Amount")}
+                                {header(Column::Assets, "Assets")}
+                                {header(Column::MinAda, "Min ADA")}
+                                {header(Column::Address, "Address")}
+                            </tr>
+                        </thead>
+                        <tbody>
+                            { for visible.iter().skip(page * PAGE_SIZE).take(PAGE_SIZE).map(|&index| {
+                                let row = &rows[index];
+                                let row_findings = findings.get(&row.id);
+                                let selected = self.selected == Some(index);
+
+                                html! {
+                                    <>
+                                        <tr role="button"
+                                            class={classes!(selected.then_some("table-active"))}
+                                            onclick={link.callback(move |_| Message::Select(index))}>
+                                            <td class="text-truncate" style="max-width: 150px;">{row.tx_hash.clone()}</td>
+                                            <td>{row.index}</td>
+                                            <td>
+                                                {row.amount.display(symbol).to_string()}
+                                                if let Some(findings) = row_findings {
+                                                    <span class="badge text-bg-warning ms-2">{findings.len()}</span>
+                                                }
+                                            </td>
+                                            <td>
+                                                if row.num_assets > 0 {
+                                                    <span class="badge text-bg-info">{format!("{} assets", row.num_assets)}</span>
+                                                }
+                                            </td>
+                                            <td>{row.min_ada(parameters).map(|min_ada| min_ada.display(symbol).to_string()).unwrap_or_default()}</td>
+                                            <td class="text-truncate" style="max-width: 250px;">{row.address.clone()}</td>
+                                        </tr>
+                                        if selected {
+                                            <tr>
+                                                <td colspan="6">
+                                                    {view_findings(row, row_findings)}
+                                                </td>
+                                            </tr>
+                                        }
+                                    </>
+                                }
+                            }) }
+                        </tbody>
+                    </table>
+                </div>
+                <div class="d-flex justify-content-between align-items-center">
+                    <span class="small text-muted">
+                        {format!("{} of {} UTxOs", visible.len(), rows.len())}
+                    </span>
+                    <div class="btn-group btn-group-sm">
+                        <button class="btn btn-outline-secondary" disabled={page == 0}
+                            onclick={link.callback(move |_| Message::Page(page.saturating_sub(1)))}>
+                            {"Previous"}
+                        </button>
+                        <span class="btn btn-outline-secondary disabled">
+                            {format!("{} / {}", page + 1, num_pages)}
+                        </span>
+                        <button class="btn btn-outline-secondary" disabled={page + 1 >= num_pages}
+                            onclick={link.callback(move |_| Message::Page(page + 1))}>
+                            {"Next"}
+                        </button>
+                    </div>
+                </div>
+            </>
+        }
+    }
+}
+
+impl UtxoTable {
+    /// filter and sort the rows again, after the sort, the filters or the
+    /// rows changed
+    fn refresh(&mut self, props: &Properties) {
+        let rows = &props.decoded.rows;
+        self.visible = (0..rows.len())
+            .filter(|&index| self.filters.matches(&rows[index], &props.findings))
+            .collect();
+        let (column, ascending) = self.sort;
+        self.visible.sort_by(|&a, &b| {
+            let ordering = column.compare(&rows[a], &rows[b]);
+            if ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        });
+    }
+}
+
+fn view_findings(row: &UtxoRow, findings: Option<&Vec<String>>) -> Html {
+    match findings {
+        None => html! {
+            <span class="text-muted small">{format!("No finding mentions {}.", row.id)}</span>
+        },
+        Some(findings) => html! {
+            <>
+                <span class="small">{format!("Findings mentioning {}:", row.id)}</span>
+                <ul class="small mb-0">
+                    { for findings.iter().map(|finding| html! { <li>{finding.clone()}</li> }) }
+                </ul>
+            </>
+        },
+    }
+}
+
+impl UtxoRow {
+    pub fn new(utxo: &Utxo, output: Option<&Output>) -> Self {
+        let num_assets = output
+            .map(|output| output.value.num_assets())
+            .unwrap_or_default();
+        Self {
+            id: utxo_id(utxo),
+            tx_hash: utxo.transaction_id().to_string(),
+            index: utxo.index() as u64,
+            amount: Lovelace::new(utxo.amount()),
+            num_assets,
+            size: output
+                .filter(|output| output.value.has_assets())
+                .map(|output| output.size),
+            address: utxo
+                .address()
+                .ok()
+                .and_then(|address| address.to_bech32().ok())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// the minimum ADA of the outputs carrying native assets
+    fn min_ada(&self, parameters: &ProtocolParameters) -> Option<Lovelace> {
+        self.size
+            .map(|size| Lovelace::new(parameters.min_ada(size)))
+    }
+}
+
+impl Filters {
+    fn matches(&self, row: &UtxoRow, findings: &UtxoFindings) -> bool {
+        (self.search.is_empty()
+            || row.tx_hash.contains(&self.search)
+            || row.address.contains(&self.search))
+            && (self.address.is_empty() || row.address.contains(&self.address))
+            && self
+                .min_amount
+                .amount()
+                .map_or(true, |min| row.amount >= min)
+            && self
+                .max_amount
+                .amount()
+                .map_or(true, |max| row.amount <= max)
+            && (!self.flagged || findings.contains_key(&row.id))
+            && (!self.has_tokens || row.num_assets > 0)
+    }
+}
+
+impl AmountFilter {
+    fn new(amount: &str) -> Self {
+        Self((!amount.trim().is_empty()).then(|| amount.parse()))
+    }
+
+    fn amount(&self) -> Option<Lovelace> {
+        self.0.and_then(Result::ok)
+    }
+
+    fn is_invalid(&self) -> bool {
+        matches!(self.0, Some(Err(_)))
+    }
+}
+
+impl Column {
+    fn compare(&self, a: &UtxoRow, b: &UtxoRow) -> Ordering {
+        match self {
+            Self::TxHash => a.tx_hash.cmp(&b.tx_hash).then(a.index.cmp(&b.index)),
+            Self::Amount => a.amount.cmp(&b.amount),
+            Self::Assets => a.num_assets.cmp(&b.num_assets),
+            // the minimum ADA grows with the size of the output
+            Self::MinAda => a.size.cmp(&b.size),
+            Self::Address => a.address.cmp(&b.address),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amount_filters() {
+        assert_eq!(AmountFilter::new("").amount(), None);
+        assert!(!AmountFilter::new(" ").is_invalid());
+        assert_eq!(
+            AmountFilter::new("1.5").amount(),
+            Some(Lovelace::new(1_500_000))
+        );
+        for amount in ["1,5", "abc"] {
+            let filter = AmountFilter::new(amount);
+            assert!(filter.is_invalid(), "{amount}");
+            assert_eq!(filter.amount(), None);
+        }
+    }
+}
@@ -1,28 +1,66 @@
-use cardano_connector::{ConnectedWallet, Utxo};
+use super::{
+    dom_scope::DomScope,
+    fragmentation::{fragmentation, Fragmentation},
+    lovelace::{Lovelace, Symbol},
+    output::{decode_output, output_size, value_size, AssetName, Output, PolicyId, Value},
+    protocol_parameters::ProtocolParameters,
+    simulator::CoinSelectionSimulator,
+    snapshot::WalletSnapshot,
+    utxo_table::{UtxoFindings, UtxoRow, UtxoTable},
+};
+use cardano_connector::Utxo;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, rc::Rc};
+use web_sys::HtmlInputElement;
 use yew::{platform::spawn_local, prelude::*};
 
-#[derive(Default)]
+/// placeholder for the lovelace of an output when estimating its size,
+/// any amount between 0.065536 and 4294.967295 ₳ is serialized in 5 bytes
+const COIN_PLACEHOLDER: u64 = 2_000_000;
+
 pub struct UtxosView {
     state: State,
+    protocol_parameters: ProtocolParameters,
+    protocol_parameters_error: Option<String>,
 }
 
 #[derive(Default)]
 enum State {
     #[default]
     Loading,
-    Loaded(Vec<Utxo>),
-    Error(String),
+    Loaded {
+        utxos: Rc<Vec<(Utxo, Option<Output>)>>,
+        decoded: Rc<DecodedUtxos>,
+    },
+}
+
+/// the outputs of the UTxOs of the snapshot and the rows of their table,
+/// in the order of the snapshot
+///
+/// Decoded by the analysis worker: decoding tens of thousands of outputs
+/// freezes the page.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DecodedUtxos {
+    pub outputs: Vec<Option<Output>>,
+    pub rows: Vec<UtxoRow>,
 }
 
 pub enum Message {
-    CollectUtxos,
-    UtxoCollected(Vec<Utxo>),
-    Error(String),
+    LoadProtocolParameters(gloo::file::File),
+    ProtocolParametersLoaded(ProtocolParameters),
+    ProtocolParametersError(String),
 }
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct UtxosProperties {
-    pub wallet: ConnectedWallet,
+    /// `None` while the wallet is being scanned
+    pub snapshot: Option<Rc<WalletSnapshot>>,
+    /// `None` until the analysis worker decoded the UTxOs of the snapshot
+    pub decoded: Option<Rc<DecodedUtxos>>,
+    /// the findings of the checks, to show the ones mentioning a UTxO
+    pub findings: Rc<UtxoFindings>,
+    #[prop_or_default]
+    pub symbol: Symbol,
 }
 
 impl Component for UtxosView {
@@ -30,49 +68,64 @@ impl Component for UtxosView {
     type Properties = UtxosProperties;
 
     fn create(ctx: &Context<Self>) -> Self {
-        ctx.link().send_message(Message::CollectUtxos);
-
-        UtxosView::default()
+        UtxosView {
+            state: State::new(ctx.props()),
+            protocol_parameters: ProtocolParameters::load(),
+            protocol_parameters_error: None,
+        }
     }
 
     fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
-        if ctx.props().ne(old_props) {
-            ctx.link().send_message(Message::CollectUtxos);
-            true
-        } else {
-            false
+        // the findings and the symbol only change how the UTxOs are displayed
+        let props = ctx.props();
+        if props.snapshot != old_props.snapshot || props.decoded != old_props.decoded {
+            self.state = State::new(props);
         }
+        ctx.props().ne(old_props)
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            Message::CollectUtxos => {
+            Message::LoadProtocolParameters(file) => {
                 let link = ctx.link().clone();
-                let wallet = ctx.props().wallet.clone();
 
                 spawn_local(async move {
-                    match wallet.all_utxos(None).await {
-                        Ok(utxos) => link.send_message(Message::UtxoCollected(utxos)),
-                        Err(error) => link.send_message(Message::Error(error.to_string())),
+                    let result = gloo::file::futures::read_as_text(&file)
+                        .await
+                        .map_err(|error| error.to_string())
+                        .and_then(|text| {
+                            serde_json::from_str::<ProtocolParameters>(&text)
+                                .map_err(|error| error.to_string())
+                        });
+
+                    match result {
+                        Ok(parameters) => {
+                            link.send_message(Message::ProtocolParametersLoaded(parameters))
+                        }
+                        Err(error) => link.send_message(Message::ProtocolParametersError(error)),
                     }
                 });
-
-                let prev = std::mem::replace(&mut self.state, State::Loading);
-
-                !matches!(prev, State::Loading)
+                false
             }
-            Message::UtxoCollected(utxos) => {
-                self.state = State::Loaded(utxos);
+            Message::ProtocolParametersLoaded(parameters) => {
+                if let Err(error) = parameters.save() {
+                    gloo::console::error!(error.to_string());
+                }
+                self.protocol_parameters = parameters;
+                self.protocol_parameters_error = None;
                 true
             }
-            Message::Error(error) => {
-                self.state = State::Error(error);
+            Message::ProtocolParametersError(error) => {
+                self.protocol_parameters_error = Some(error);
                 true
             }
         }
     }
 
-    fn view(&self, _ctx: &Context<Self>) -> Html {
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let symbol = ctx.props().symbol;
+        let scope = DomScope::of(ctx);
+
         match &self.state {
             State::Loading => html! {
                 <div class="d-flex align-items-center mt-3">
@@ -82,10 +135,16 @@ impl Component for UtxosView {
                     <span>{"Loading UTXOs..."}</span>
                 </div>
             },
-            State::Loaded(utxos) => {
+            State::Loaded { utxos, decoded } => {
                 let total_utxos = utxos.len();
-                let total_value: u64 = utxos.iter().map(|utxo| utxo.amount()).sum();
-                let total_value_ada = total_value as f64 / 1_000_000.0;
+                let total_value: Lovelace = utxos
+                    .iter()
+                    .map(|(utxo, _)| Lovelace::new(utxo.amount()))
+                    .sum();
+                let assets = aggregate_assets(utxos);
+                let locked_ada = locked_ada(&self.protocol_parameters, utxos);
+                let locked_ada_value = locked_ada.locked.display(symbol);
+                let freed_ada_value = locked_ada.freed().display(symbol);
 
                 html! {
                     <div class="mt-4">
@@ -97,65 +156,335 @@ impl Component for UtxosView {
                                     <p class="card-text fs-3">{total_utxos}</p>
                                 </div>
                             </div>
-                            <div class="card" style="flex: 1">
+                            <div class="card me-2" style="flex: 1">
                                 <div class="card-body">
                                     <h5 class="card-title">{"Total Value"}</h5>
-                                    <p class="card-text fs-3">{format!("{:.2} ₳", total_value_ada)}</p>
+                                    <p class="card-text fs-3">{total_value.display(symbol).to_string()}</p>
+                                </div>
+                            </div>
+                            <div class="card me-2" style="flex: 1">
+                                <div class="card-body">
+                                    <h5 class="card-title">{"Native Assets"}</h5>
+                                    <p class="card-text fs-3">{assets.values().map(BTreeMap::len).sum::<usize>()}</p>
+                                </div>
+                            </div>
+                            <div class="card" style="flex: 1">
+                                <div class="card-body">
+                                    <h5 class="card-title">{"Locked min-ADA"}</h5>
+                                    <p class="card-text fs-3">{locked_ada_value.to_string()}</p>
                                 </div>
                             </div>
                         </div>
-                        <div class="accordion" id="utxoAccordion">
+                        if locked_ada.num_utxos > 0 {
+                            <p class="text-muted">
+                                {format!(
+                                    "{} are locked as minimum ADA by the {} UTxOs carrying native assets: this ADA can only be spent together with the tokens. ",
+                                    locked_ada_value,
+                                    locked_ada.num_utxos,
+                                )}
+                                if locked_ada.freed() > Lovelace::ZERO {
+                                    {format!(
+                                        "Merging the tokens into {} output(s) would free about {}.",
+                                        locked_ada.num_outputs,
+                                        freed_ada_value,
+                                    )}
+                                }
+                            </p>
+                        }
+                        {self.view_protocol_parameters(ctx)}
+                        {view_fragmentation(&fragmentation(&self.protocol_parameters, utxos), total_utxos, symbol)}
+                        <CoinSelectionSimulator utxos={utxos.clone()} parameters={self.protocol_parameters.clone()} {symbol} />
+                        <div class="accordion" id={scope.id("utxoAccordion")}>
                             <div class="accordion-item">
                                 <h2 class="accordion-header">
-                                    <button class="accordion-button collapsed" type="button" data-bs-toggle="collapse" data-bs-target="#utxoList">
+                                    <button class="accordion-button collapsed" type="button" data-bs-toggle="collapse" data-bs-target={scope.target("utxoList")}>
                                         {"UTXO Details"}
                                     </button>
                                 </h2>
-                                <div id="utxoList" class="accordion-collapse collapse">
+                                <div id={scope.id("utxoList")} class="accordion-collapse collapse">
                                     <div class="accordion-body">
-                                        <div class="table-responsive">
-                                            <table class="table table-hover">
-                                                <thead>
-                                                    <tr>
-                                                        <th>{"TX Hash"}</th>
-                                                        <th>{"Output Index"}</th>
-                                                        <th>{"Amount"}</th>
-                                                        <th>{"Address"}</th>
-                                                    </tr>
-                                                </thead>
-                                                <tbody>
-                                                    { for utxos.iter().map(|utxo| {
-                                                        let tx_hash = utxo.transaction_id().to_string();
-                                                        let output_index = utxo.index();
-                                                        let amount = utxo.amount() as f64 / 1_000_000.0;
-                                                        let address = &utxo.address().unwrap().to_bech32().unwrap();
-
-                                                        html! {
-                                                            <tr>
-                                                                <td class="text-truncate" style="max-width: 150px;">{tx_hash}</td>
-                                                                <td>{output_index}</td>
-                                                                <td>{format!("{:.6} ₳", amount)}</td>
-                                                                <td class="text-truncate" style="max-width: 250px;">{address}</td>
-                                                            </tr>
-                                                        }
-                                                    }) }
-                                                </tbody>
-                                            </table>
-                                        </div>
+                                        <UtxoTable
+                                            decoded={decoded.clone()}
+                                            parameters={self.protocol_parameters.clone()}
+                                            findings={ctx.props().findings.clone()}
+                                            {symbol} />
                                     </div>
                                 </div>
                             </div>
+                            if !assets.is_empty() {
+                                <div class="accordion-item">
+                                    <h2 class="accordion-header">
+                                        <button class="accordion-button collapsed" type="button" data-bs-toggle="collapse" data-bs-target={scope.target("assetList")}>
+                                            {"Native Assets per Policy"}
+                                        </button>
+                                    </h2>
+                                    <div id={scope.id("assetList")} class="accordion-collapse collapse">
+                                        <div class="accordion-body">
+                                            <div class="table-responsive">
+                                                <table class="table table-hover">
+                                                    <thead>
+                                                        <tr>
+                                                            <th>{"Policy ID"}</th>
+                                                            <th>{"Asset Name"}</th>
+                                                            <th>{"Asset Name (hex)"}</th>
+                                                            <th>{"Quantity"}</th>
+                                                            <th>{"UTXOs"}</th>
+                                                        </tr>
+                                                    </thead>
+                                                    <tbody>
+                                                        { for assets.iter().flat_map(|(policy_id, assets)| {
+                                                            assets.iter().map(move |(name, (quantity, num_utxos))| html! {
+                                                                <tr>
+                                                                    <td class="text-truncate" style="max-width: 150px;">{policy_id.to_string()}</td>
+                                                                    <td>{name.as_utf8().unwrap_or_default().to_owned()}</td>
+                                                                    <td class="text-truncate" style="max-width: 150px;">{name.to_hex()}</td>
+                                                                    <td>{quantity.to_string()}</td>
+                                                                    <td>{num_utxos.to_string()}</td>
+                                                                </tr>
+                                                            })
+                                                        }) }
+                                                    </tbody>
+                                                </table>
+                                            </div>
+                                        </div>
+                                    </div>
+                                </div>
+                            }
                         </div>
 
                     </div>
                 }
             }
-            State::Error(error) => html! {
-                <div class="alert alert-danger mt-3" role="alert">
-                    <h5 class="alert-heading">{"Error Loading UTXOs"}</h5>
-                    <p>{format!("{}", error)}</p>
-                </div>
-            },
         }
     }
 }
+
+impl State {
+    /// the UTxOs of the snapshot with the outputs decoded by the worker
+    fn new(props: &UtxosProperties) -> Self {
+        let (Some(snapshot), Some(decoded)) = (&props.snapshot, &props.decoded) else {
+            return Self::Loading;
+        };
+
+        let utxos = snapshot
+            .utxos
+            .iter()
+            .cloned()
+            .zip(decoded.outputs.iter().cloned())
+            .collect();
+        Self::Loaded {
+            utxos: Rc::new(utxos),
+            decoded: decoded.clone(),
+        }
+    }
+}
+
+impl DecodedUtxos {
+    pub fn new(utxos: &[Utxo]) -> Self {
+        let outputs: Vec<_> = utxos
+            .iter()
+            .map(|utxo| {
+                decode_output(utxo)
+                    .map_err(|error| gloo::console::error!(error.to_string()))
+                    .ok()
+            })
+            .collect();
+        let rows = utxos
+            .iter()
+            .zip(&outputs)
+            .map(|(utxo, output)| UtxoRow::new(utxo, output.as_ref()))
+            .collect();
+
+        Self { outputs, rows }
+    }
+}
+
+/// decoded once per analysis, comparing the contents would be costly
+impl PartialEq for DecodedUtxos {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl UtxosView {
+    fn view_protocol_parameters(&self, ctx: &Context<Self>) -> Html {
+        let scope = DomScope::of(ctx);
+        let onchange = ctx.link().batch_callback(|event: Event| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            input
+                .files()
+                .and_then(|files| files.get(0))
+                .map(|file| Message::LoadProtocolParameters(gloo::file::File::from(file)))
+        });
+
+        html! {
+            <div class="mb-3">
+                <label for={scope.id("protocol-parameters-file")} class="form-label small">
+                    {format!(
+                        "Protocol parameters: {} lovelace per UTxO byte, max tx size {} bytes. Load the output of `cardano-cli query protocol-parameters` to use other values:",
+                        self.protocol_parameters.utxo_cost_per_byte,
+                        self.protocol_parameters.max_tx_size,
+                    )}
+                </label>
+                <input id={scope.id("protocol-parameters-file")} class="form-control form-control-sm" type="file" accept="application/json" onchange={onchange} />
+                if let Some(error) = &self.protocol_parameters_error {
+                    <div class="text-danger small mt-1">{format!("Could not load the protocol parameters: {error}")}</div>
+                }
+            </div>
+        }
+    }
+}
+
+/// the minimum ADA locked by the UTxOs carrying native assets
+struct LockedAda {
+    /// number of UTxOs carrying native assets
+    num_utxos: usize,
+    /// the minimum ADA currently required by these UTxOs
+    locked: Lovelace,
+    /// the number of outputs needed to hold all the native assets
+    num_outputs: usize,
+    /// the minimum ADA required if all the native assets were merged
+    merged: Lovelace,
+}
+
+impl LockedAda {
+    fn freed(&self) -> Lovelace {
+        self.locked
+            .checked_sub(self.merged)
+            .unwrap_or(Lovelace::ZERO)
+    }
+}
+
+fn locked_ada(parameters: &ProtocolParameters, utxos: &[(Utxo, Option<Output>)]) -> LockedAda {
+    let outputs = utxos
+        .iter()
+        .filter_map(|(_, output)| output.as_ref())
+        .filter(|output| output.value.has_assets())
+        .collect::<Vec<_>>();
+
+    let locked = outputs
+        .iter()
+        .map(|output| Lovelace::new(parameters.min_ada(output.size)))
+        .sum();
+
+    // pack the policies into as few outputs as the max value size allows
+    let mut all_assets = Value::default();
+    for output in &outputs {
+        all_assets.add(&Value {
+            coin: 0,
+            assets: output.value.assets.clone(),
+        });
+    }
+
+    let mut packed = Vec::new();
+    let mut current = Value {
+        coin: COIN_PLACEHOLDER,
+        ..Value::default()
+    };
+    for (policy_id, assets) in all_assets.assets {
+        let mut candidate = current.clone();
+        candidate.assets.insert(policy_id.clone(), assets.clone());
+
+        if current.has_assets() && value_size(&candidate) as u64 > parameters.max_value_size {
+            packed.push(std::mem::replace(
+                &mut current,
+                Value {
+                    coin: COIN_PLACEHOLDER,
+                    assets: BTreeMap::from([(policy_id, assets)]),
+                },
+            ));
+        } else {
+            current = candidate;
+        }
+    }
+    if current.has_assets() {
+        packed.push(current);
+    }
+
+    let address = outputs
+        .first()
+        .map(|output| output.address.as_slice())
+        .unwrap_or_default();
+    let merged = packed
+        .iter()
+        .map(|value| Lovelace::new(parameters.min_ada(output_size(address, value))))
+        .sum();
+
+    LockedAda {
+        num_utxos: outputs.len(),
+        locked,
+        num_outputs: packed.len(),
+        merged,
+    }
+}
+
+fn view_fragmentation(fragmentation: &Fragmentation, total_utxos: usize, symbol: Symbol) -> Html {
+    let spend_all = &fragmentation.spend_all;
+    let fee = Lovelace::new(spend_all.fee).display(symbol);
+
+    html! {
+        <div class="card mb-3">
+            <div class="card-body">
+                <h5 class="card-title">{format!("Fragmentation score: {}/100", fragmentation.score)}</h5>
+                <table class="table table-sm">
+                    <tbody>
+                        { for fragmentation.histogram.iter().map(|(label, count)| {
+                            let percent = if total_utxos == 0 { 0 } else { count * 100 / total_utxos };
+                            html! {
+                                <tr>
+                                    <td style="width: 120px;">{format!("{label} {symbol}")}</td>
+                                    <td>
+                                        <div class="progress" role="progressbar">
+                                            <div class="progress-bar" style={format!("width: {percent}%")}></div>
+                                        </div>
+                                    </td>
+                                    <td style="width: 60px;" class="text-end">{count.to_string()}</td>
+                                </tr>
+                            }
+                        }) }
+                    </tbody>
+                </table>
+                if spend_all.num_transactions > 1 {
+                    <div class="alert alert-warning mb-0" role="alert">
+                        {format!(
+                            "Spending your full balance would need a transaction of about {} bytes: your wallet would need at least {} transactions to be emptied. Consider consolidating your UTxOs.",
+                            spend_all.tx_size,
+                            spend_all.num_transactions,
+                        )}
+                    </div>
+                } else {
+                    <p class="card-text text-muted mb-0">
+                        {format!(
+                            "Your full balance can be spent in a single transaction of about {} bytes, for about {} of fees.",
+                            spend_all.tx_size,
+                            fee,
+                        )}
+                    </p>
+                }
+            </div>
+        </div>
+    }
+}
+
+/// total quantity and number of UTxOs holding each asset, grouped by policy
+fn aggregate_assets(
+    utxos: &[(Utxo, Option<Output>)],
+) -> BTreeMap<PolicyId, BTreeMap<AssetName, (u64, usize)>> {
+    let mut assets: BTreeMap<PolicyId, BTreeMap<AssetName, (u64, usize)>> = BTreeMap::new();
+
+    for (policy_id, name, quantity) in utxos
+        .iter()
+        .filter_map(|(_, output)| output.as_ref())
+        .flat_map(|output| output.value.assets())
+    {
+        let (total, num_utxos) = assets
+            .entry(policy_id.clone())
+            .or_default()
+            .entry(name.clone())
+            .or_default();
+        *total = total.saturating_add(quantity);
+        *num_utxos += 1;
+    }
+
+    assets
+}
@@ -0,0 +1,143 @@
+use super::{ReportStatus, ReportView};
+use crate::wallet::{
+    lovelace::{Lovelace, Symbol},
+    tasks::accounts::{Account, AccountsReport},
+};
+use std::borrow::Cow;
+use yew::prelude::*;
+
+#[derive(Default)]
+pub struct AccountsView;
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    pub report: Option<AccountsReport>,
+    #[prop_or_default]
+    pub symbol: Symbol,
+}
+
+pub type Message = ();
+
+impl Component for AccountsView {
+    type Message = Message;
+    type Properties = Properties;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self::default()
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        ctx.props() != old_props
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let report = &ctx.props().report;
+        let symbol = ctx.props().symbol;
+
+        let status: ReportStatus;
+        let children: Html;
+
+        match report {
+            None => {
+                status = ReportStatus::Pending {
+                    msg: Cow::Borrowed("Grouping your funds by account..."),
+                };
+                children = html!();
+            }
+            Some(report) if report.multiple_accounts() => {
+                let num_accounts = report.known().count();
+
+                status = ReportStatus::Warning {
+                    msg: format!("Your wallet returned {num_accounts} reward addresses where one is expected.").into(),
+                };
+                children = html! {
+                    <>
+                        <p>
+                            {"A wallet connected to an application shares a single account, with a single stake key. "}
+                            {"This wallet shares several of them: check that you connected the account you meant to, "}
+                            {"the funds of the other accounts are exposed to this page as well."}
+                        </p>
+                        {view_accounts(report, symbol)}
+                    </>
+                };
+            }
+            Some(report) => match report.known().next() {
+                Some(account) => {
+                    status = ReportStatus::Success {
+                        msg: format!(
+                            "Your wallet uses a single account: {} on {} UTxOs.",
+                            balance(account).display(symbol),
+                            account.utxos.len(),
+                        )
+                        .into(),
+                    };
+                    children = html!();
+                }
+                None => {
+                    status = ReportStatus::Warning {
+                        msg: Cow::Borrowed("Your wallet did not return any reward address."),
+                    };
+                    children = html! {
+                        <>
+                            <p>{"Without a reward address the funds of the wallet cannot be attributed to an account."}</p>
+                            {view_accounts(report, symbol)}
+                        </>
+                    };
+                }
+            },
+        }
+
+        html! {
+            <ReportView id="accounts" status={status}>
+                {children}
+            </ReportView>
+        }
+    }
+}
+
+fn balance(account: &Account) -> Lovelace {
+    account
+        .utxos
+        .iter()
+        .map(|utxo| Lovelace::new(utxo.amount()))
+        .sum()
+}
+
+fn view_accounts(report: &AccountsReport, symbol: Symbol) -> Html {
+    html! {
+        <table class="table table-hover">
+            <thead>
+                <tr>
+                    <th>{"Reward address"}</th>
+                    <th>{"Addresses"}</th>
+                    <th>{"UTxOs"}</th>
+                    <th>{"Amount"}</th>
+                </tr>
+            </thead>
+            <tbody>
+                { for report.accounts.iter().map(|account| {
+                    let reward_address = match &account.reward_address {
+                        Some(address) => html! {
+                            <>
+                                {address.to_bech32().unwrap_or_default()}
+                                if !account.known {
+                                    <span class="badge text-bg-warning ms-2">{"not returned by the wallet"}</span>
+                                }
+                            </>
+                        },
+                        None => html! { <span class="text-muted">{"No stake key"}</span> },
+                    };
+
+                    html! {
+                        <tr>
+                            <td class="text-truncate" style="max-width: 250px;">{reward_address}</td>
+                            <td>{account.addresses.len()}</td>
+                            <td>{account.utxos.len()}</td>
+                            <td>{balance(account).display(symbol).to_string()}</td>
+                        </tr>
+                    }
+                }) }
+            </tbody>
+        </table>
+    }
+}
@@ -0,0 +1,84 @@
+use super::{ReportStatus, ReportView};
+use crate::wallet::{
+    lovelace::Symbol,
+    tasks::change_address::{ChangeAddressIssue, ChangeAddressReport},
+};
+use std::borrow::Cow;
+use yew::prelude::*;
+
+#[derive(Default)]
+pub struct ChangeAddressView;
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    pub report: Option<ChangeAddressReport>,
+    #[prop_or_default]
+    pub symbol: Symbol,
+}
+
+pub type Message = ();
+
+impl Component for ChangeAddressView {
+    type Message = Message;
+    type Properties = Properties;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self::default()
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        ctx.props() != old_props
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let report = &ctx.props().report;
+
+        let status: ReportStatus;
+        let children: Html;
+
+        match report {
+            None => {
+                status = ReportStatus::Pending {
+                    msg: Cow::Borrowed("Checking the change address..."),
+                };
+                children = html!();
+            }
+            Some(report) if report.issues.is_empty() => {
+                status = ReportStatus::Success {
+                    msg: Cow::Borrowed("Your change address is a fresh address of your account."),
+                };
+                children = html!();
+            }
+            Some(report) => {
+                let msg = match report.issues.as_slice() {
+                    [ChangeAddressIssue::Reused { .. }] => {
+                        Cow::Borrowed("Your wallet sends the change to an address it already used.")
+                    }
+                    issues => format!("Your change address has {} issue(s).", issues.len()).into(),
+                };
+                status = ReportStatus::Warning { msg };
+                children = html! {
+                    <>
+                        <p>
+                            {"The change of your transactions goes back to "}
+                            <span class="font-monospace text-break">
+                                {report.address.to_bech32().unwrap_or_else(|_| report.address.to_hex())}
+                            </span>
+                            {". It should be an address of your account, delegating to your stake key, that was not used before: "}
+                            {"sending the change to the same address over and over lets anyone link your transactions together."}
+                        </p>
+                        <ul>
+                            { for report.issues.iter().map(|issue| html! { <li>{issue.describe(ctx.props().symbol)}</li> }) }
+                        </ul>
+                    </>
+                };
+            }
+        }
+
+        html! {
+            <ReportView id="change-address" status={status}>
+                {children}
+            </ReportView>
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use super::{ReportStatus, ReportView};
+use crate::wallet::{
+    lovelace::{Lovelace, Symbol},
+    tasks::collateral::{CollateralIssue, CollateralReport},
+};
+use std::borrow::Cow;
+use yew::prelude::*;
+
+#[derive(Default)]
+pub struct CollateralView;
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    pub report: Option<CollateralReport>,
+    #[prop_or_default]
+    pub symbol: Symbol,
+}
+
+pub type Message = ();
+
+impl Component for CollateralView {
+    type Message = Message;
+    type Properties = Properties;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self::default()
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        ctx.props() != old_props
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let report = &ctx.props().report;
+
+        let status: ReportStatus;
+        let children: Html;
+
+        match report {
+            None => {
+                status = ReportStatus::Pending {
+                    msg: Cow::Borrowed("Checking the collateral..."),
+                };
+                children = html!();
+            }
+            Some(report) if report.issues.is_empty() => {
+                let total: Lovelace = report
+                    .collateral
+                    .iter()
+                    .map(|utxo| Lovelace::new(utxo.amount()))
+                    .sum();
+
+                status = ReportStatus::Success {
+                    msg: format!(
+                        "Your collateral is healthy: {} UTxO(s) for {}.",
+                        report.collateral.len(),
+                        total.display(ctx.props().symbol),
+                    )
+                    .into(),
+                };
+                children = html!();
+            }
+            Some(report) => {
+                let msg = match report.issues.as_slice() {
+                    [CollateralIssue::Unavailable { .. }] => {
+                        Cow::Borrowed("Your wallet failed to return its collateral.")
+                    }
+                    [CollateralIssue::NotSet] => {
+                        Cow::Borrowed("Your wallet has no collateral set.")
+                    }
+                    issues => format!("Your collateral has {} issue(s).", issues.len()).into(),
+                };
+                status = ReportStatus::Warning { msg };
+                children = html! {
+                    <>
+                        <p>
+                            {"The collateral is used to pay the fees of a failing smart contract transaction. "}
+                            {"It should be a small ADA-only UTxO, at a key address, belonging to your wallet."}
+                        </p>
+                        <ul>
+                            { for report.issues.iter().map(|issue| html! { <li>{issue.describe(ctx.props().symbol)}</li> }) }
+                        </ul>
+                    </>
+                };
+            }
+        }
+
+        html! {
+            <ReportView id="collateral" status={status}>
+                {children}
+            </ReportView>
+        }
+    }
+}
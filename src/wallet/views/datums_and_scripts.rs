@@ -0,0 +1,119 @@
+use super::{ReportStatus, ReportView};
+use crate::wallet::{
+    lovelace::{Lovelace, Symbol},
+    output::{Datum, Output},
+};
+use cardano_connector::Utxo;
+use std::borrow::Cow;
+use yew::prelude::*;
+
+#[derive(Default)]
+pub struct DatumsAndScriptsView;
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    pub utxos: Option<Vec<(Utxo, Output)>>,
+    #[prop_or_default]
+    pub symbol: Symbol,
+}
+
+pub type Message = ();
+
+impl Component for DatumsAndScriptsView {
+    type Message = Message;
+    type Properties = Properties;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self::default()
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        ctx.props() != old_props
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let utxos = &ctx.props().utxos;
+        let symbol = ctx.props().symbol;
+
+        let status: ReportStatus;
+        let children: Html;
+
+        match utxos {
+            None => {
+                status = ReportStatus::Pending {
+                    msg: Cow::Borrowed("Searching for UTxOs with datums or reference scripts..."),
+                };
+                children = html!();
+            }
+            Some(utxos) if utxos.is_empty() => {
+                status = ReportStatus::Success {
+                    msg: Cow::Borrowed("You don't have UTxOs with datums or reference scripts."),
+                };
+                children = html!();
+            }
+            Some(utxos) => {
+                let num_utxos = utxos.len();
+                let total: Lovelace = utxos
+                    .iter()
+                    .map(|(utxo, _)| Lovelace::new(utxo.amount()))
+                    .sum();
+                let total_ada = total.display(symbol);
+
+                status = ReportStatus::Warning {
+                    msg: format!("You have {num_utxos} UTxOs carrying a datum or a reference script, locking {total_ada}.").into(),
+                };
+                children = html! {
+                    <>
+                        <p>
+                            {"Ordinary wallet UTxOs don't need datums nor reference scripts. "}
+                            {"They increase the minimum ADA of the UTxO and are often leftovers of dApp interactions. "}
+                            {"If you don't recognise them they may be part of an attack: don't interact with dApps they point you to."}
+                        </p>
+                        <table class="table table-hover">
+                            <thead>
+                                <tr>
+                                    <th>{"TX Hash"}</th>
+                                    <th>{"Output Index"}</th>
+                                    <th>{"Datum"}</th>
+                                    <th>{"Reference Script"}</th>
+                                    <th>{"Amount"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                { for utxos.iter().map(|(utxo, output)| {
+                                    let datum = match &output.datum {
+                                        None => String::new(),
+                                        Some(Datum::Hash(hash)) => format!("hash {}", hex::encode(hash)),
+                                        Some(Datum::Inline { size }) => format!("inline, {size} bytes"),
+                                    };
+                                    let script = output
+                                        .script_ref
+                                        .as_ref()
+                                        .map(|script| format!("{}, {} bytes", script.language, script.size))
+                                        .unwrap_or_default();
+                                    let amount = Lovelace::new(utxo.amount());
+
+                                    html! {
+                                        <tr>
+                                            <td class="text-truncate" style="max-width: 150px;">{utxo.transaction_id().to_string()}</td>
+                                            <td>{utxo.index()}</td>
+                                            <td class="text-truncate" style="max-width: 200px;">{datum}</td>
+                                            <td>{script}</td>
+                                            <td>{amount.display(symbol).to_string()}</td>
+                                        </tr>
+                                    }
+                                }) }
+                            </tbody>
+                        </table>
+                    </>
+                };
+            }
+        }
+
+        html! {
+            <ReportView id="datums-and-scripts" status={status}>
+                {children}
+            </ReportView>
+        }
+    }
+}
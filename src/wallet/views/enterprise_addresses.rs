@@ -0,0 +1,104 @@
+use super::{ReportStatus, ReportView};
+use crate::wallet::lovelace::{Lovelace, Symbol};
+use cardano_connector::{Address, Utxo};
+use std::{borrow::Cow, collections::HashMap};
+use yew::prelude::*;
+
+#[derive(Default)]
+pub struct EnterpriseAddressesView;
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    pub utxos: Option<HashMap<Address, Vec<Utxo>>>,
+    #[prop_or_default]
+    pub symbol: Symbol,
+}
+
+pub type Message = ();
+
+impl Component for EnterpriseAddressesView {
+    type Message = Message;
+    type Properties = Properties;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self::default()
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        ctx.props() != old_props
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let utxos = &ctx.props().utxos;
+        let symbol = ctx.props().symbol;
+
+        let status: ReportStatus;
+        let children: Html;
+
+        match utxos {
+            None => {
+                status = ReportStatus::Pending {
+                    msg: Cow::Borrowed("Searching for funds on Enterprise Addresses..."),
+                };
+                children = html!();
+            }
+            Some(utxos) if utxos.is_empty() => {
+                status = ReportStatus::Success {
+                    msg: Cow::Borrowed("You don't have funds on Enterprise Addresses."),
+                };
+                children = html!();
+            }
+            Some(utxos) => {
+                let num_enterprise_addresses = utxos.len();
+                let num_utxos = utxos.iter().fold(0, |count, (_, vec)| count + vec.len());
+                let total: Lovelace = utxos
+                    .values()
+                    .flatten()
+                    .map(|utxo| Lovelace::new(utxo.amount()))
+                    .sum();
+                let total_ada = total.display(symbol);
+
+                status = ReportStatus::Warning {
+                    msg: format!("You have {total_ada} on {num_enterprise_addresses} Enterprise Addresses across {num_utxos} UTxOs that cannot be delegated.").into(),
+                };
+                children = html! {
+                    <>
+                        <p>
+                            {"Enterprise addresses don't have a stake part: the funds they hold do not participate in staking nor in governance. "}
+                            {format!("{total_ada} are currently not earning rewards. ")}
+                            {"Unless these addresses are used on purpose (exchanges, scripts...), move the funds to one of your base addresses."}
+                        </p>
+                        <table class="table table-hover">
+                            <thead>
+                                <tr>
+                                    <th>{"Address"}</th>
+                                    <th>{"UTxOs"}</th>
+                                    <th>{"Amount"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                { for utxos.iter().map(|(address, utxos)| {
+                                    let amount: Lovelace = utxos.iter().map(|utxo| Lovelace::new(utxo.amount())).sum();
+
+                                    html! {
+                                        <tr>
+                                            <td class="text-truncate" style="max-width: 250px;">{address.to_bech32().unwrap_or_default()}</td>
+                                            <td>{utxos.len()}</td>
+                                            <td>{amount.display(symbol).to_string()}</td>
+                                        </tr>
+                                    }
+                                }) }
+                            </tbody>
+                        </table>
+                    </>
+                };
+            }
+        }
+
+        html! {
+            <ReportView id="enterprise-addresses" status={status}>
+                {children}
+            </ReportView>
+        }
+    }
+}
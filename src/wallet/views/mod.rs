@@ -1,14 +1,26 @@
+pub mod accounts;
 pub mod byron_address;
+pub mod change_address;
+pub mod collateral;
+pub mod datums_and_scripts;
+pub mod enterprise_addresses;
 pub mod inconsistent_reward_addresses;
+pub mod pointer_addresses;
+pub mod script_credentials;
+pub mod spam_tokens;
 pub mod used_addresses;
 
+use super::dom_scope::DomScope;
 use crate::icons::{Bug, CheckMark, Warning};
 use std::borrow::Cow;
 
 pub use self::{
-    byron_address::ByronAddressView,
+    accounts::AccountsView, byron_address::ByronAddressView, change_address::ChangeAddressView,
+    collateral::CollateralView, datums_and_scripts::DatumsAndScriptsView,
+    enterprise_addresses::EnterpriseAddressesView,
     inconsistent_reward_addresses::InconsistentRewardAddressesView,
-    used_addresses::UsedAddressesView,
+    pointer_addresses::PointerAddressesView, script_credentials::ScriptCredentialsView,
+    spam_tokens::SpamTokensView, used_addresses::UsedAddressesView,
 };
 use yew::prelude::*;
 
@@ -49,7 +61,7 @@ impl Component for ReportView {
             id,
         } = ctx.props();
 
-        let id = format!("{id}-accordion");
+        let id = DomScope::of(ctx).id(&format!("{id}-accordion"));
 
         let header: Html = match status {
             ReportStatus::Pending { msg } => {
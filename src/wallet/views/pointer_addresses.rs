@@ -0,0 +1,120 @@
+use super::{ReportStatus, ReportView};
+use crate::wallet::lovelace::{Lovelace, Symbol};
+use cardano_connector::{Address, Utxo};
+use pallas_addresses::ShelleyDelegationPart;
+use std::{borrow::Cow, collections::HashMap};
+use yew::prelude::*;
+
+#[derive(Default)]
+pub struct PointerAddressesView;
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    pub utxos: Option<HashMap<Address, Vec<Utxo>>>,
+    #[prop_or_default]
+    pub symbol: Symbol,
+}
+
+pub type Message = ();
+
+impl Component for PointerAddressesView {
+    type Message = Message;
+    type Properties = Properties;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self::default()
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        ctx.props() != old_props
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let utxos = &ctx.props().utxos;
+        let symbol = ctx.props().symbol;
+
+        let status: ReportStatus;
+        let children: Html;
+
+        match utxos {
+            None => {
+                status = ReportStatus::Pending {
+                    msg: Cow::Borrowed("Searching for Pointer Addresses..."),
+                };
+                children = html!();
+            }
+            Some(utxos) if utxos.is_empty() => {
+                status = ReportStatus::Success {
+                    msg: Cow::Borrowed("You don't have Pointer Addresses."),
+                };
+                children = html!();
+            }
+            Some(utxos) => {
+                let num_pointer_addresses = utxos.len();
+                let num_utxos = utxos.iter().fold(0, |count, (_, vec)| count + vec.len());
+                let total: Lovelace = utxos
+                    .values()
+                    .flatten()
+                    .map(|utxo| Lovelace::new(utxo.amount()))
+                    .sum();
+
+                status = ReportStatus::Warning {
+                    msg: format!("You have {num_pointer_addresses} Pointer Addresses holding {} across {num_utxos} UTxOs that are not earning rewards.", total.display(symbol)).into(),
+                };
+                children = html! {
+                    <>
+                        <p>
+                            {"Since the Conway era, pointer addresses no longer delegate stake. "}
+                            {"The funds held on these addresses do not earn staking rewards and do not count towards your voting power. "}
+                            {"Send them to one of your base addresses (an address with a stake key) to have them participate again."}
+                        </p>
+                        <table class="table table-hover">
+                            <thead>
+                                <tr>
+                                    <th>{"Address"}</th>
+                                    <th>{"Pointer (slot, tx, cert)"}</th>
+                                    <th>{"UTxOs"}</th>
+                                    <th>{"Amount"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                { for utxos.iter().map(|(address, utxos)| view_address(address, utxos, symbol)) }
+                            </tbody>
+                        </table>
+                    </>
+                };
+            }
+        }
+
+        html! {
+            <ReportView id="pointer-addresses" status={status}>
+                {children}
+            </ReportView>
+        }
+    }
+}
+
+fn view_address(address: &Address, utxos: &[Utxo], symbol: Symbol) -> Html {
+    let pointer = match address {
+        Address::Shelley(shelley) => match shelley.delegation() {
+            ShelleyDelegationPart::Pointer(pointer) => format!(
+                "({}, {}, {})",
+                pointer.slot(),
+                pointer.tx_idx(),
+                pointer.cert_idx()
+            ),
+            _ => String::new(),
+        },
+        _ => String::new(),
+    };
+    let amount: Lovelace = utxos.iter().map(|utxo| Lovelace::new(utxo.amount())).sum();
+
+    html! {
+        <tr>
+            <td class="text-truncate" style="max-width: 250px;">{address.to_bech32().unwrap_or_default()}</td>
+            <td>{pointer}</td>
+            <td>{utxos.len()}</td>
+            <td>{amount.display(symbol).to_string()}</td>
+        </tr>
+    }
+}
@@ -0,0 +1,133 @@
+use super::{ReportStatus, ReportView};
+use crate::wallet::lovelace::{Lovelace, Symbol};
+use crate::wallet::tasks::credentials::{classify, Credential};
+use cardano_connector::{Address, Utxo};
+use std::{borrow::Cow, collections::HashMap};
+use yew::prelude::*;
+
+#[derive(Default)]
+pub struct ScriptCredentialsView;
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    pub utxos: Option<HashMap<Address, Vec<Utxo>>>,
+    #[prop_or_default]
+    pub symbol: Symbol,
+}
+
+pub type Message = ();
+
+impl Component for ScriptCredentialsView {
+    type Message = Message;
+    type Properties = Properties;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self::default()
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        ctx.props() != old_props
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let utxos = &ctx.props().utxos;
+        let symbol = ctx.props().symbol;
+
+        let status: ReportStatus;
+        let children: Html;
+
+        match utxos {
+            None => {
+                status = ReportStatus::Pending {
+                    msg: Cow::Borrowed("Searching for script locked UTxOs..."),
+                };
+                children = html!();
+            }
+            Some(utxos) if utxos.is_empty() => {
+                status = ReportStatus::Success {
+                    msg: Cow::Borrowed("All your UTxOs are locked by key hashes."),
+                };
+                children = html!();
+            }
+            Some(utxos) => {
+                let (locked, delegated): (Vec<_>, Vec<_>) = utxos
+                    .iter()
+                    .partition(|(address, _)| classify(address).0 == Credential::ScriptHash);
+                let num_locked_utxos = locked.iter().fold(0, |count, (_, vec)| count + vec.len());
+                let locked_total: Lovelace = locked
+                    .iter()
+                    .flat_map(|(_, utxos)| utxos.iter())
+                    .map(|utxo| Lovelace::new(utxo.amount()))
+                    .sum();
+                let locked_total_ada = locked_total.display(symbol);
+
+                if locked.is_empty() {
+                    let num_utxos = delegated
+                        .iter()
+                        .fold(0, |count, (_, vec)| count + vec.len());
+                    status = ReportStatus::Warning {
+                        msg: format!(
+                            "You have {num_utxos} UTxOs staked with a script stake credential."
+                        )
+                        .into(),
+                    };
+                } else {
+                    status = ReportStatus::Warning {
+                        msg: format!("Your wallet returned {num_locked_utxos} script locked UTxOs holding {locked_total_ada}.").into(),
+                    };
+                }
+
+                children = html! {
+                    <>
+                        if !locked.is_empty() {
+                            <p>
+                                {"Some of the UTxOs returned by your wallet are locked by a script (multisig, smart contract...). "}
+                                {"The wallet cannot spend them with its own keys only: it needs the script and extra witnesses. "}
+                                {"Selecting them as inputs in a transaction will fail unless these are provided."}
+                            </p>
+                        }
+                        if !delegated.is_empty() {
+                            <p>
+                                {"Some of your UTxOs are staked with a script stake credential: "}
+                                {"withdrawing their rewards or changing their delegation requires the script's witnesses."}
+                            </p>
+                        }
+                        <table class="table table-hover">
+                            <thead>
+                                <tr>
+                                    <th>{"Address"}</th>
+                                    <th>{"Payment Credential"}</th>
+                                    <th>{"Stake Credential"}</th>
+                                    <th>{"UTxOs"}</th>
+                                    <th>{"Amount"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                { for utxos.iter().map(|(address, utxos)| {
+                                    let (payment, stake) = classify(address);
+                                    let amount: Lovelace = utxos.iter().map(|utxo| Lovelace::new(utxo.amount())).sum();
+
+                                    html! {
+                                        <tr>
+                                            <td class="text-truncate" style="max-width: 250px;">{address.to_bech32().unwrap_or_default()}</td>
+                                            <td>{payment.to_string()}</td>
+                                            <td>{stake.to_string()}</td>
+                                            <td>{utxos.len()}</td>
+                                            <td>{amount.display(symbol).to_string()}</td>
+                                        </tr>
+                                    }
+                                }) }
+                            </tbody>
+                        </table>
+                    </>
+                };
+            }
+        }
+
+        html! {
+            <ReportView id="script-credentials" status={status}>
+                {children}
+            </ReportView>
+        }
+    }
+}
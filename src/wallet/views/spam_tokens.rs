@@ -0,0 +1,180 @@
+use super::{ReportStatus, ReportView};
+use crate::wallet::{
+    dom_scope::DomScope,
+    lovelace::{Lovelace, Symbol},
+    tasks::spam::SuspiciousUtxo,
+    token_lists::TokenLists,
+};
+use std::borrow::Cow;
+use web_sys::HtmlInputElement;
+use yew::{platform::spawn_local, prelude::*};
+
+#[derive(Default)]
+pub struct SpamTokensView {
+    error: Option<String>,
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    pub utxos: Option<Vec<SuspiciousUtxo>>,
+    /// emitted once the user added their own token lists
+    pub on_token_lists_changed: Callback<()>,
+    #[prop_or_default]
+    pub symbol: Symbol,
+}
+
+pub enum Message {
+    LoadTokenLists(gloo::file::File),
+    TokenListsLoaded,
+    Error(String),
+}
+
+impl Component for SpamTokensView {
+    type Message = Message;
+    type Properties = Properties;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self::default()
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        ctx.props() != old_props
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::LoadTokenLists(file) => {
+                let link = ctx.link().clone();
+
+                spawn_local(async move {
+                    let result = gloo::file::futures::read_as_text(&file)
+                        .await
+                        .map_err(|error| error.to_string())
+                        .and_then(|text| {
+                            serde_json::from_str::<TokenLists>(&text)
+                                .map_err(|error| error.to_string())
+                        })
+                        .and_then(|lists| {
+                            TokenLists::save_user(lists).map_err(|error| error.to_string())
+                        });
+
+                    match result {
+                        Ok(()) => link.send_message(Message::TokenListsLoaded),
+                        Err(error) => link.send_message(Message::Error(error)),
+                    }
+                });
+                false
+            }
+            Message::TokenListsLoaded => {
+                self.error = None;
+                ctx.props().on_token_lists_changed.emit(());
+                true
+            }
+            Message::Error(error) => {
+                self.error = Some(error);
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let scope = DomScope::of(ctx);
+        let utxos = &ctx.props().utxos;
+        let symbol = ctx.props().symbol;
+
+        let status: ReportStatus;
+        let children: Html;
+
+        match utxos {
+            None => {
+                status = ReportStatus::Pending {
+                    msg: Cow::Borrowed("Searching for spam tokens and unlisted addresses..."),
+                };
+                children = html!();
+            }
+            Some(utxos) if utxos.is_empty() => {
+                status = ReportStatus::Success {
+                    msg: Cow::Borrowed(
+                        "You don't have UTxOs with spam tokens or at unlisted addresses.",
+                    ),
+                };
+                children = html!();
+            }
+            Some(utxos) => {
+                let num_utxos = utxos.len();
+
+                status = ReportStatus::Warning {
+                    msg: format!("You have {num_utxos} UTxOs with suspicious tokens or at unlisted addresses.")
+                        .into(),
+                };
+                children = html! {
+                    <>
+                        <p>
+                            {"Airdropped tokens are a common phishing vector: never follow the links or instructions found in their names. "}
+                            {"Do not interact with the tokens you don't recognise."}
+                        </p>
+                        <p>
+                            {"The small UTxOs at addresses your wallet does not list may be sent by anyone, "}
+                            {"or be yours if the wallet did not return all its addresses."}
+                        </p>
+                        <table class="table table-hover">
+                            <thead>
+                                <tr>
+                                    <th>{"TX Hash"}</th>
+                                    <th>{"Output Index"}</th>
+                                    <th>{"Amount"}</th>
+                                    <th>{"Reasons"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                { for utxos.iter().map(|SuspiciousUtxo { utxo, reasons }| {
+                                    let amount = Lovelace::new(utxo.amount());
+
+                                    html! {
+                                        <tr>
+                                            <td class="text-truncate" style="max-width: 150px;">{utxo.transaction_id().to_string()}</td>
+                                            <td>{utxo.index()}</td>
+                                            <td>{amount.display(symbol).to_string()}</td>
+                                            <td>
+                                                <ul class="list-unstyled mb-0">
+                                                    { for reasons.iter().map(|reason| html! { <li>{reason.describe(symbol)}</li> }) }
+                                                </ul>
+                                            </td>
+                                        </tr>
+                                    }
+                                }) }
+                            </tbody>
+                        </table>
+                    </>
+                };
+            }
+        }
+
+        let onchange = ctx.link().batch_callback(|event: Event| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            input
+                .files()
+                .and_then(|files| files.get(0))
+                .map(|file| Message::LoadTokenLists(gloo::file::File::from(file)))
+        });
+
+        // outside of the report: the lists can be added before the check
+        // completes, and whatever its result
+        html! {
+            <>
+                <ReportView id="spam-tokens" status={status}>
+                    {children}
+                </ReportView>
+                <div class="mt-2 mb-3">
+                    <label for={scope.id("token-lists-file")} class="form-label">
+                        {"Add your own allow and deny lists (JSON, same format as the bundled list):"}
+                    </label>
+                    <input id={scope.id("token-lists-file")} class="form-control form-control-sm" type="file" accept="application/json" onchange={onchange} />
+                    if let Some(error) = &self.error {
+                        <div class="text-danger small mt-1">{format!("Could not load the token lists: {error}")}</div>
+                    }
+                </div>
+            </>
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! notice when the wallet switches to another account or network
+//!
+//! The user can switch account or network in the extension while the page
+//! is open, the results of the scan are then about another wallet. The
+//! wallets implementing the experimental `accountChange` and
+//! `networkChange` events tell us, the other ones are polled for their
+//! network id and reward addresses.
+
+use super::{
+    api::Api,
+    cancel::{deadline, CancelToken, Interrupted},
+    cip30::WalletApi,
+    network_id::NetworkId,
+    snapshot::WalletSnapshot,
+};
+use cardano_connector::Address;
+use js_sys::{Function, Promise, Reflect};
+use std::collections::HashSet;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+/// milliseconds between two polls of the wallet
+const POLL_INTERVAL: u32 = 10_000;
+
+/// the experimental CIP-30 events telling the account or network changed
+const EVENTS: [&str; 2] = ["accountChange", "networkChange"];
+
+/// how the change was noticed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletChange {
+    /// the wallet sent an `accountChange` or `networkChange` event
+    Event,
+    Network,
+    Account,
+}
+
+/// what identifies the account the snapshot was taken of
+struct Fingerprint {
+    /// `None` until the wallet answers `getNetworkId`
+    network_id: Option<NetworkId>,
+    /// the order the wallet lists them in does not matter
+    reward_addresses: HashSet<Address>,
+}
+
+/// wait until the wallet switches account or network, or the token is
+/// cancelled
+///
+/// The `api` should not record its calls: the polls would fill the API
+/// activity panel.
+pub async fn watch(
+    wallet: &WalletApi,
+    api: &Api,
+    snapshot: &WalletSnapshot,
+    cancel: &CancelToken,
+) -> Result<WalletChange, Interrupted> {
+    let mut fingerprint = Fingerprint {
+        network_id: snapshot.network_id.clone().ok(),
+        reward_addresses: snapshot.reward_addresses.iter().cloned().collect(),
+    };
+    let events = Events::listen(wallet);
+
+    loop {
+        let event = async {
+            match &events {
+                Some(events) => {
+                    let _ = JsFuture::from(events.promise.clone()).await;
+                }
+                None => std::future::pending().await,
+            }
+        };
+        match deadline(event, POLL_INTERVAL, cancel).await {
+            Ok(()) => return Ok(WalletChange::Event),
+            Err(Interrupted::Cancelled) => return Err(Interrupted::Cancelled),
+            Err(Interrupted::Timeout) => (),
+        }
+
+        // the errors are ignored, e.g. the wallet is locked for a moment
+        let network_id = api
+            .network_id()
+            .await
+            .ok()
+            .and_then(|response| response.to_network_id().ok());
+        // the network id of the scan is unknown if the call failed then:
+        // the first one the wallet returns is the one compared with
+        match (fingerprint.network_id, network_id) {
+            (Some(known), Some(network_id)) if known != network_id => {
+                return Ok(WalletChange::Network);
+            }
+            (None, Some(network_id)) => fingerprint.network_id = Some(network_id),
+            _ => (),
+        }
+        let reward_addresses = api
+            .reward_addresses()
+            .await
+            .ok()
+            .and_then(|response| response.to_addresses().ok());
+        if let Some(reward_addresses) = reward_addresses {
+            if reward_addresses.into_iter().collect::<HashSet<_>>() != fingerprint.reward_addresses
+            {
+                return Ok(WalletChange::Account);
+            }
+        }
+    }
+}
+
+/// the listeners registered on the events of the wallet
+struct Events {
+    experimental: JsValue,
+    /// resolved by the first event
+    promise: Promise,
+    listener: Function,
+}
+
+impl Events {
+    /// `None` if the wallet does not implement the events
+    fn listen(wallet: &WalletApi) -> Option<Self> {
+        let experimental = wallet.experimental()?;
+        let on: Function = Reflect::get(&experimental, &"on".into())
+            .ok()?
+            .dyn_into()
+            .ok()?;
+
+        let mut listener = None;
+        let promise = Promise::new(&mut |resolve, _reject| listener = Some(resolve));
+        let listener = listener?;
+        for event in EVENTS {
+            on.call2(&experimental, &event.into(), &listener).ok()?;
+        }
+
+        Some(Self {
+            experimental,
+            promise,
+            listener,
+        })
+    }
+}
+
+impl Drop for Events {
+    fn drop(&mut self) {
+        let off = Reflect::get(&self.experimental, &"off".into())
+            .ok()
+            .and_then(|off| off.dyn_into::<Function>().ok());
+        if let Some(off) = off {
+            for event in EVENTS {
+                let _ = off.call2(&self.experimental, &event.into(), &self.listener);
+            }
+        }
+    }
+}